@@ -2,18 +2,22 @@
 #![no_std]
 
 extern crate alloc;
-use alloc::{boxed::Box, collections::BTreeSet, format, vec, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeSet, format, string::String, vec, vec::Vec};
 use casper_contract::{
     contract_api::{account, runtime, storage, system},
     unwrap_or_revert::UnwrapOrRevert,
 };
 use casper_types::{
     runtime_args, AccessRights, CLType, CLTyped, CLValue, ContractHash, ContractPackageHash,
-    EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Group, Key, Parameter, RuntimeArgs,
-    URef, U256, U512,
+    EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Group, Key, Parameter, PublicKey,
+    RuntimeArgs, URef, U256, U512,
 };
 use casperlabs_contract_utils::{ContractContext, OnChainContractStorage};
-use liquidity_transformer_crate::{self, data, LIQUIDITYTRANSFORMER};
+use liquidity_transformer_crate::{
+    self, data,
+    data::{AllowlistProof, KycApproval},
+    LIQUIDITYTRANSFORMER,
+};
 
 #[derive(Default)]
 struct LiquidityTransformer(OnChainContractStorage);
@@ -39,6 +43,13 @@ impl LiquidityTransformer {
         package_hash: Key,
         contract_hash: Key,
         purse: URef,
+        investment_days: u8,
+        max_supply: U256,
+        max_invest: U256,
+        token_cost: U256,
+        refund_cap: U256,
+        vesting_cliff_days: u64,
+        vesting_days: u64,
     ) {
         LIQUIDITYTRANSFORMER::init(
             self,
@@ -51,6 +62,13 @@ impl LiquidityTransformer {
             package_hash,
             contract_hash,
             purse,
+            investment_days,
+            max_supply,
+            max_invest,
+            token_cost,
+            refund_cap,
+            vesting_cliff_days,
+            vesting_days,
         );
     }
 }
@@ -66,6 +84,13 @@ fn constructor() {
     let package_hash: ContractPackageHash = runtime::get_named_arg("package_hash");
     let contract_hash: ContractHash = runtime::get_named_arg("contract_hash");
     let purse: URef = runtime::get_named_arg("purse");
+    let investment_days: u8 = runtime::get_named_arg("investment_days");
+    let max_supply: U256 = runtime::get_named_arg("max_supply");
+    let max_invest: U256 = runtime::get_named_arg("max_invest");
+    let token_cost: U256 = runtime::get_named_arg("token_cost");
+    let refund_cap: U256 = runtime::get_named_arg("refund_cap");
+    let vesting_cliff_days: u64 = runtime::get_named_arg("vesting_cliff_days");
+    let vesting_days: u64 = runtime::get_named_arg("vesting_days");
 
     LiquidityTransformer::default().constructor(
         wise,
@@ -77,6 +102,38 @@ fn constructor() {
         Key::from(package_hash),
         Key::from(contract_hash),
         purse,
+        investment_days,
+        max_supply,
+        max_invest,
+        token_cost,
+        refund_cap,
+        vesting_cliff_days,
+        vesting_days,
+    );
+}
+
+/// @dev Called by the installer when a new version is added to the package, creates the
+///     storage the older version is missing and keeps everything already stored
+#[no_mangle]
+fn upgrade() {
+    let contract_hash: ContractHash = runtime::get_named_arg("contract_hash");
+    let investment_days: u8 = runtime::get_named_arg("investment_days");
+    let max_supply: U256 = runtime::get_named_arg("max_supply");
+    let max_invest: U256 = runtime::get_named_arg("max_invest");
+    let token_cost: U256 = runtime::get_named_arg("token_cost");
+    let refund_cap: U256 = runtime::get_named_arg("refund_cap");
+    let vesting_cliff_days: u64 = runtime::get_named_arg("vesting_cliff_days");
+    let vesting_days: u64 = runtime::get_named_arg("vesting_days");
+
+    LiquidityTransformer::default().upgrade(
+        Key::from(contract_hash),
+        investment_days,
+        max_supply,
+        max_invest,
+        token_cost,
+        refund_cap,
+        vesting_cliff_days,
+        vesting_days,
     );
 }
 
@@ -91,6 +148,239 @@ fn set_settings() {
     LiquidityTransformer::default().set_settings(wise_token, pair_wise, pair_scspr, synthetic_cspr);
 }
 
+/// @notice Sets intermediate tokens used by prepare_path for a token
+/// @dev can be only called by keeper
+/// @param token_address ERC20 token address to be swapped for CSPR
+/// @param intermediate_tokens tokens to route through between token_address and WCSPR
+#[no_mangle]
+fn set_swap_route() {
+    let token_address: Key = runtime::get_named_arg("token_address");
+    let intermediate_tokens: Vec<Key> = runtime::get_named_arg("intermediate_tokens");
+
+    LiquidityTransformer::default().set_swap_route(token_address, intermediate_tokens);
+}
+
+/// @notice Adds, updates or removes a token accepted by reserve_wise_with_token
+/// @dev can be only called by keeper
+/// @param token_address ERC20 token address
+/// @param accepted whether the token can be used for reservations
+/// @param min_amount minimum token amount per reservation
+/// @param max_amount maximum token amount accepted over the sale, zero for no cap
+#[no_mangle]
+fn set_accepted_token() {
+    let token_address: Key = runtime::get_named_arg("token_address");
+    let accepted: bool = runtime::get_named_arg("accepted");
+    let min_amount: U256 = runtime::get_named_arg("min_amount");
+    let max_amount: U256 = runtime::get_named_arg("max_amount");
+
+    LiquidityTransformer::default().set_accepted_token(
+        token_address,
+        accepted,
+        min_amount,
+        max_amount,
+    );
+}
+
+/// @notice Shows registry entry of a token
+/// @param token_address ERC20 token address
+/// @return ((accepted, min_amount, max_amount), total_amount)
+#[no_mangle]
+fn accepted_token() {
+    let token_address: Key = runtime::get_named_arg("token_address");
+
+    let ret: ((bool, U256, U256), U256) =
+        LiquidityTransformer::default().accepted_token(token_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Sets percentage of reserved tokens credited to referrers
+/// @dev can be only called by keeper
+/// @param referral_bonus bonus percentage, at most 100
+#[no_mangle]
+fn set_referral_bonus() {
+    let referral_bonus: U256 = runtime::get_named_arg("referral_bonus");
+
+    LiquidityTransformer::default().set_referral_bonus(referral_bonus);
+}
+
+/// @notice Percentage of reserved tokens credited to referrers
+#[no_mangle]
+fn referral_bonus() {
+    runtime::ret(CLValue::from_t(data::referral_bonus()).unwrap_or_revert());
+}
+
+/// @notice Sets cash back, token bonus and lock period of an investment mode
+/// @dev can be only called by keeper before the first reservation
+/// @param investment_mode mode to configure, below 6
+/// @param cash_back_rate percentage of the investment paid back as cash back
+/// @param token_bonus percentage of purchased tokens added as bonus
+/// @param lock_days days after the investment phase until tokens can be claimed
+#[no_mangle]
+fn set_investment_mode() {
+    let investment_mode: u8 = runtime::get_named_arg("investment_mode");
+    let cash_back_rate: U256 = runtime::get_named_arg("cash_back_rate");
+    let token_bonus: U256 = runtime::get_named_arg("token_bonus");
+    let lock_days: u64 = runtime::get_named_arg("lock_days");
+
+    LiquidityTransformer::default().set_investment_mode(
+        investment_mode,
+        cash_back_rate,
+        token_bonus,
+        lock_days,
+    );
+}
+
+/// @notice Selects how reserved tokens are priced
+/// @dev can be only called by keeper before the first reservation
+/// @param sale_mode 0 for the fixed TOKEN_COST price, 1 to split a fixed
+///     supply per investment day pro rata among that day's contributors,
+///     2 to price tokens on the bonding curve,
+///     0 needs TOKEN_COST == MAX_INVEST / (MAX_SUPPLY / 1E9)
+#[no_mangle]
+fn set_sale_mode() {
+    let sale_mode: u8 = runtime::get_named_arg("sale_mode");
+
+    LiquidityTransformer::default().set_sale_mode(sale_mode);
+}
+
+/// @notice Configures the bonding curve used in sale mode 2
+/// @dev can be only called by keeper before the first reservation,
+///     the curve starts at TOKEN_COST and follows TOTAL_TRANSFER_TOKENS
+/// @param curve_type 0 for linear, 1 for exponential
+/// @param factor linear: price increase per token sold,
+///     exponential: price increase in percent per step
+/// @param step exponential only: tokens sold between price increases
+#[no_mangle]
+fn set_bonding_curve() {
+    let curve_type: u8 = runtime::get_named_arg("curve_type");
+    let factor: U256 = runtime::get_named_arg("factor");
+    let step: U256 = runtime::get_named_arg("step");
+
+    LiquidityTransformer::default().set_bonding_curve(curve_type, factor, step);
+}
+
+/// @notice Sets the minimum CSPR the sale has to raise
+/// @dev can be only called by keeper before the first reservation, below the
+///     soft cap forward_liquidity is blocked and refunds open after the investment days
+/// @param soft_cap minimum TOTAL_CSPR_CONTRIBUTED, zero to disable
+#[no_mangle]
+fn set_soft_cap() {
+    let soft_cap: U256 = runtime::get_named_arg("soft_cap");
+
+    LiquidityTransformer::default().set_soft_cap(soft_cap);
+}
+
+/// @notice Restricts reservations to the investors of a Merkle allowlist
+/// @dev can be only called by keeper, leaves are (address, cap) pairs
+/// @param allowlist_root hex encoded Merkle root, None opens reservations to everyone
+#[no_mangle]
+fn set_allowlist_root() {
+    let allowlist_root: Option<String> = runtime::get_named_arg("allowlist_root");
+
+    LiquidityTransformer::default().set_allowlist_root(allowlist_root);
+}
+
+/// @notice Registers the secp256k1 key that signs KYC approvals
+/// @dev can be only called by keeper
+/// @param kyc_signer public key of the off-chain compliance signer
+#[no_mangle]
+fn set_kyc_signer() {
+    let kyc_signer: PublicKey = runtime::get_named_arg("kyc_signer");
+
+    LiquidityTransformer::default().set_kyc_signer(kyc_signer);
+}
+
+/// @notice Turns compliance mode on or off
+/// @dev can be only called by keeper, reservations need a signed KYC approval while enabled
+/// @param compliance_mode true to require KYC approvals
+#[no_mangle]
+fn set_compliance_mode() {
+    let compliance_mode: bool = runtime::get_named_arg("compliance_mode");
+
+    LiquidityTransformer::default().set_compliance_mode(compliance_mode);
+}
+
+/// @notice Sets the contribution limits applied to every investor
+/// @dev can be only called by keeper, zero disables a limit
+/// @param min_investment minimum total CSPR an investor has to reach
+/// @param max_investment maximum total CSPR an investor can reserve
+/// @param daily_cap maximum CSPR an investor can reserve on a single day
+#[no_mangle]
+fn set_investor_limits() {
+    let min_investment: U256 = runtime::get_named_arg("min_investment");
+    let max_investment: U256 = runtime::get_named_arg("max_investment");
+    let daily_cap: U256 = runtime::get_named_arg("daily_cap");
+
+    LiquidityTransformer::default().set_investor_limits(min_investment, max_investment, daily_cap);
+}
+
+/// @notice Sets the penalty charged by withdraw_reservation
+/// @dev can be only called by keeper
+/// @param withdraw_penalty percentage of the withdrawn CSPR kept by the contract, at most 100
+#[no_mangle]
+fn set_withdraw_penalty() {
+    let withdraw_penalty: U256 = runtime::get_named_arg("withdraw_penalty");
+
+    LiquidityTransformer::default().set_withdraw_penalty(withdraw_penalty);
+}
+
+/// @notice Sets how far the WISE/SCSPR pool price may be from the sale price
+/// @dev can be only called by keeper, forward_liquidity reverts outside this range
+///     and uses it for the add_liquidity minimums, defaults to 1
+/// @param price_tolerance allowed deviation in percent, at most 100
+#[no_mangle]
+fn set_price_tolerance() {
+    let price_tolerance: U256 = runtime::get_named_arg("price_tolerance");
+
+    LiquidityTransformer::default().set_price_tolerance(price_tolerance);
+}
+
+/// @notice Sets where the LP tokens minted by forward_liquidity are sent
+/// @dev can be only called by keeper before forward_liquidity
+/// @param lp_destination 0 to burn, 1 for a treasury, 2 for an LP locker contract,
+///     3 to keep them for investors to claim with claim_lp
+/// @param lp_recipient treasury or LP locker address, ignored when burning,
+///     a locker must hold the pair_wise LP token and unlock after the current day
+#[no_mangle]
+fn set_lp_destination() {
+    let lp_destination: u8 = runtime::get_named_arg("lp_destination");
+    let lp_recipient: Key = runtime::get_named_arg("lp_recipient");
+
+    LiquidityTransformer::default().set_lp_destination(lp_destination, lp_recipient);
+}
+
+/// @notice Shows settings of an investment mode
+/// @param investment_mode mode to look up
+/// @return (cash_back_rate, token_bonus, lock_days)
+#[no_mangle]
+fn investment_mode() {
+    let investment_mode: u8 = runtime::get_named_arg("investment_mode");
+
+    let ret: (U256, U256, u64) = LiquidityTransformer::default().investment_mode(investment_mode);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows totals reserved through an investment mode
+/// @param investment_mode mode to look up
+/// @return ((cspr_contributed, purchased_tokens), (cash_back, bonus_tokens))
+#[no_mangle]
+fn investment_mode_totals() {
+    let investment_mode: u8 = runtime::get_named_arg("investment_mode");
+
+    let ret: ((U256, U256), (U256, U256)) =
+        LiquidityTransformer::default().investment_mode_totals(investment_mode);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Cancels the sale so every investor can request a refund right away
+/// @dev can be only called by keeper before forward_liquidity,
+///     blocks new reservations and forward_liquidity,
+///     in daily sale mode the open days are settled on refund
+#[no_mangle]
+fn cancel_sale() {
+    LiquidityTransformer::default().cancel_sale();
+}
+
 /// @notice Use to renounce_keeper and can be only called by keeper
 /// @dev Sets settings_keeper to zero address
 #[no_mangle]
@@ -99,37 +389,75 @@ fn renounce_keeper() {
 }
 
 /// @dev Performs reservation of WISE tokens with CSPR
+/// @param referral_address optional address credited with the referral bonus
+/// @param beneficiary optional address credited with the reservation, caller if None
+/// @param kyc_approval ((max_amount, expiry, nonce), signature) required in compliance mode
+/// @param allowlist_proof (cap, proof) required while an allowlist root is set
 #[no_mangle]
 fn reserve_wise() {
     let investment_mode: u8 = runtime::get_named_arg("investment_mode");
     let msg_value: U256 = runtime::get_named_arg("msg_value");
+    let referral_address: Option<Key> = runtime::get_named_arg("referral_address");
+    let beneficiary: Option<Key> = runtime::get_named_arg("beneficiary");
+    let kyc_approval: Option<KycApproval> = runtime::get_named_arg("kyc_approval");
+    let allowlist_proof: Option<AllowlistProof> = runtime::get_named_arg("allowlist_proof");
     let caller_purse: URef = runtime::get_named_arg("caller_purse");
 
-    LiquidityTransformer::default().reserve_wise(investment_mode, msg_value, caller_purse);
+    LiquidityTransformer::default().reserve_wise(
+        investment_mode,
+        msg_value,
+        referral_address,
+        beneficiary,
+        kyc_approval,
+        allowlist_proof,
+        caller_purse,
+    );
 }
 
 /// @notice Allows reservation of WISE tokens with other ERC20 tokens
 /// @dev this will require LT contract to be approved as spender
 /// @param token_address address of an ERC20 token to use
 /// @param token_amount amount of tokens to use for reservation
+/// @param min_cspr_out minimum amount of CSPR the swap must return
+/// @param deadline blocktime after which the swap is rejected
+/// @param path optional swap route ending in WCSPR, prepare_path is used if None
+/// @param referral_address optional address credited with the referral bonus
+/// @param beneficiary optional address credited with the reservation, caller if None
+/// @param kyc_approval ((max_amount, expiry, nonce), signature) required in compliance mode
+/// @param allowlist_proof (cap, proof) required while an allowlist root is set
 #[no_mangle]
 fn reserve_wise_with_token() {
     let token_address: Key = runtime::get_named_arg("token_address");
     let token_amount: U256 = runtime::get_named_arg("token_amount");
     let investment_mode: u8 = runtime::get_named_arg("investment_mode");
+    let min_cspr_out: U256 = runtime::get_named_arg("min_cspr_out");
+    let deadline: U256 = runtime::get_named_arg("deadline");
+    let path: Option<Vec<Key>> = runtime::get_named_arg("path");
+    let referral_address: Option<Key> = runtime::get_named_arg("referral_address");
+    let beneficiary: Option<Key> = runtime::get_named_arg("beneficiary");
+    let kyc_approval: Option<KycApproval> = runtime::get_named_arg("kyc_approval");
+    let allowlist_proof: Option<AllowlistProof> = runtime::get_named_arg("allowlist_proof");
     let caller_purse: URef = runtime::get_named_arg("caller_purse");
 
     LiquidityTransformer::default().reserve_wise_with_token(
         token_address,
         token_amount,
         investment_mode,
+        min_cspr_out,
+        deadline,
+        path,
+        referral_address,
+        beneficiary,
+        kyc_approval,
+        allowlist_proof,
         caller_purse,
     );
 }
 
 /// @notice Creates initial liquidity on uniswap by forwarding
 ///     reserved tokens equivalent to CSPR contributed to the contract
-/// @dev check add_liquidity documentation
+/// @dev check add_liquidity documentation, dust reserves in pair_wise are topped up
+///     to the sale price and synced before the liquidity is added
 #[no_mangle]
 fn forward_liquidity() {
     LiquidityTransformer::default().forward_liquidity();
@@ -143,6 +471,45 @@ fn get_my_tokens() {
     LiquidityTransformer::default().get_my_tokens();
 }
 
+/// @notice Allows to mint all the tokens of the caller
+///     and stake them right away in the WISE contract
+/// @dev can be called after forward_liquidity(), tokens are minted
+///     to the transformer and staked on behalf of the caller
+/// @param lock_days amount of days the stake is locked for
+/// @param referrer referrer of the stake
+/// @return stake_id id of the created stake
+#[no_mangle]
+fn get_my_tokens_and_stake() {
+    let lock_days: u64 = runtime::get_named_arg("lock_days");
+    let referrer: Key = runtime::get_named_arg("referrer");
+
+    let ret: Vec<u32> =
+        LiquidityTransformer::default().get_my_tokens_and_stake(lock_days, referrer);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Transfers the caller's share of the LP tokens kept by forward_liquidity
+/// @dev can be called once per investor after forward_liquidity()
+///     when the LP destination is set to investors
+/// @return amount of LP tokens sent to the caller
+#[no_mangle]
+fn claim_lp() {
+    let ret: U256 = LiquidityTransformer::default().claim_lp();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Credits an investor with its share of every closed investment day
+/// @dev only used in daily sale mode, payout_investor_address settles automatically
+/// @param investor_address address of the investor to settle
+/// @return tokens purchased, bonus and referral tokens credited by this settlement
+#[no_mangle]
+fn settle_investor() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().settle_investor(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
 /// @notice Allows to mint tokens for specific investor address
 /// @dev aggregades investors tokens across all investment days
 ///     and uses STAKEABLE_CONTRACT instance to mint all the WISE tokens
@@ -156,6 +523,29 @@ fn payout_investor_address() {
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
+/// @notice Mints reserved tokens for a batch of investors
+/// @dev walks UNIQUE_INVESTORS and then REFERRERS from start (or the stored cursor
+///     when start is None), the cursor only advances when the batch starts at it,
+///     investors whose payout is still locked are skipped and claim with get_my_tokens
+/// @param start index of the first investor to pay out
+/// @param count maximum amount of investors to process
+/// @return tokens_paid amount minted to the investors of the batch
+#[no_mangle]
+fn payout_investors_batch() {
+    let start: Option<U256> = runtime::get_named_arg("start");
+    let count: U256 = runtime::get_named_arg("count");
+
+    let ret: U256 = LiquidityTransformer::default().payout_investors_batch(start, count);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows index of the next investor to be paid out by payout_investors_batch
+#[no_mangle]
+fn payout_cursor() {
+    let ret: U256 = LiquidityTransformer::default().payout_cursor();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
 /// @notice Prepares path variable for uniswap to exchange tokens
 /// @dev used in reserve_wise_with_token() swap_exact_tokens_for_tokens call
 /// @param token_address ERC20 token address to be swapped for CSPR
@@ -177,7 +567,7 @@ fn current_stakeable_day() {
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
-// @notice Allows refunds if funds are stuck
+// @notice Allows refunds if funds are stuck or the sale ended below the soft cap
 #[no_mangle]
 fn request_refund() {
     let caller_purse: URef = runtime::get_named_arg("caller_purse");
@@ -186,6 +576,179 @@ fn request_refund() {
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
+/// @notice Cancels or reduces the caller's reservation during the investment phase
+/// @dev purchased and bonus tokens are reduced pro rata, the penalty and the
+///     cash back already paid on the withdrawn part are kept by the contract,
+///     a partial withdrawal must leave at least the investor minimum
+/// @param amount CSPR to withdraw from the investor balance
+/// @param caller_purse purse receiving the CSPR
+/// @return payout CSPR sent back to the caller
+#[no_mangle]
+fn withdraw_reservation() {
+    let amount: U256 = runtime::get_named_arg("amount");
+    let caller_purse: URef = runtime::get_named_arg("caller_purse");
+
+    let ret: U256 = LiquidityTransformer::default().withdraw_reservation(amount, caller_purse);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Moves the withdraw penalties kept by the contract to the treasury
+/// @dev only callable by the keeper, every penalty is swept only once
+/// @param treasury_purse purse receiving the penalties
+/// @return amount CSPR sent to the treasury
+#[no_mangle]
+fn sweep_withdraw_penalties() {
+    let treasury_purse: URef = runtime::get_named_arg("treasury_purse");
+
+    let ret: U256 = LiquidityTransformer::default().sweep_withdraw_penalties(treasury_purse);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Sends the WISE and SCSPR left over by forward_liquidity to a recipient
+/// @dev only callable by the keeper after forward_liquidity, every leftover is swept only once
+/// @param recipient address receiving the tokens
+/// @return (wise_amount, scspr_amount) tokens sent to the recipient
+#[no_mangle]
+fn sweep_dust() {
+    let recipient: Key = runtime::get_named_arg("recipient");
+
+    let ret: (U256, U256) = LiquidityTransformer::default().sweep_dust(recipient);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows the WISE and SCSPR kept by the transformer after forward_liquidity
+/// @return (wise_amount, scspr_amount) not yet swept
+#[no_mangle]
+fn dust() {
+    let ret: (U256, U256) = LiquidityTransformer::default().dust();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows amount of bonus WISE tokens earned through investment modes
+/// @param investor_address address of the investor
+/// @return bonus_tokens tokens still to be minted to the investor
+#[no_mangle]
+fn bonus_tokens() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().bonus_tokens(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows amount of WISE tokens earned by a referrer
+/// @param referral_address address of the referrer
+/// @return referral_tokens tokens still to be minted to the referrer
+#[no_mangle]
+fn referral_tokens() {
+    let referral_address: Key = runtime::get_named_arg("referral_address");
+
+    let ret: U256 = LiquidityTransformer::default().referral_tokens(referral_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows stakes created through get_my_tokens_and_stake
+/// @param investor_address address of the investor
+/// @return list of (stake_id, payout) in the order they were staked
+#[no_mangle]
+fn payout_stakes() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: Vec<(Vec<u32>, U256)> =
+        LiquidityTransformer::default().payout_stakes(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows amount of CSPR invested by an investor
+/// @param investor_address address of the investor
+/// @return investor_balance CSPR contributed by the investor
+#[no_mangle]
+fn investor_balance() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().investor_balance(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows amount of WISE tokens reserved by an investor
+/// @param investor_address address of the investor
+/// @return purchased_tokens tokens still to be minted to the investor
+#[no_mangle]
+fn purchased_tokens() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().purchased_tokens(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Snapshot of the global sale state
+/// @return ((cash_back_total, investor_count),
+///     (total_transfer_tokens, total_cspr_contributed), uniswap_swaped)
+#[no_mangle]
+fn globals() {
+    let ret: ((U256, U256), (U256, U256), bool) = LiquidityTransformer::default().globals();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Recomputes the sale totals from the investor dictionaries
+/// @dev pages over registry slots, meant to be queried by auditors and monitoring
+/// @param offset index of the first slot to read
+/// @param limit maximum amount of slots to read
+/// @param totals totals returned by the previous page, zero for the first page
+/// @return (((total_cspr_contributed, total_transfer_tokens, referral_tokens_total),
+///     (unique_investors, active_investors)), consistent) where the totals include this page
+///     and consistent tells if they match the globals once the last slot is read, None before
+#[no_mangle]
+fn check_invariants() {
+    let offset: U256 = runtime::get_named_arg("offset");
+    let limit: U256 = runtime::get_named_arg("limit");
+    let totals: ((U256, U256, U256), (U256, U256)) = runtime::get_named_arg("totals");
+    let ret: (((U256, U256, U256), (U256, U256)), Option<bool>) =
+        LiquidityTransformer::default().check_invariants(offset, limit, totals);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Lists investors in order of their first reservation
+/// @dev pages over registry slots, duplicates of legacy slots are left out
+/// @param offset index of the first slot to return
+/// @param limit maximum amount of slots to read
+/// @return investors addresses of the investors in the requested page
+#[no_mangle]
+fn unique_investors() {
+    let offset: U256 = runtime::get_named_arg("offset");
+    let limit: U256 = runtime::get_named_arg("limit");
+
+    let ret: Vec<Key> = LiquidityTransformer::default().unique_investors(offset, limit);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Investor registry counters
+/// @return (registry slots, unique investors, investors with a non zero balance)
+#[no_mangle]
+fn investor_counts() {
+    let ret: (U256, U256, U256) = LiquidityTransformer::default().investor_counts();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Amount of referrers that never reserved themselves
+/// @dev their slots follow the investor slots in payout_investors_batch
+#[no_mangle]
+fn referrer_count() {
+    let ret: U256 = LiquidityTransformer::default().referrer_count();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Indexes investor slots written before the registry kept an address index
+/// @dev can be only called by keeper, reservations are blocked until all slots are migrated
+/// @param count maximum amount of slots to migrate in this call
+/// @return migrated amount of unique investors indexed
+#[no_mangle]
+fn migrate_investor_registry() {
+    let count: U256 = runtime::get_named_arg("count");
+
+    let ret: U256 = LiquidityTransformer::default().migrate_investor_registry(count);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
 /// @notice Used for sending funds to contract
 /// @dev used as a fallback function
 #[no_mangle]
@@ -205,106 +768,740 @@ fn contract_read_only_purse() {
     );
 }
 
-fn get_entry_points() -> EntryPoints {
-    let mut entry_points = EntryPoints::new();
-    entry_points.add_entry_point(EntryPoint::new(
-        "constructor",
-        vec![
-            Parameter::new("wise", Key::cl_type()),
-            Parameter::new("scspr", Key::cl_type()),
-            Parameter::new("pair_wise", Key::cl_type()),
-            Parameter::new("pair_scspr", Key::cl_type()),
-            Parameter::new("uniswap_router", Key::cl_type()),
-            Parameter::new("wcspr", Key::cl_type()),
-            Parameter::new("package_hash", ContractPackageHash::cl_type()),
-            Parameter::new("contract_hash", ContractHash::cl_type()),
-            Parameter::new("purse", URef::cl_type()),
-        ],
-        <()>::cl_type(),
-        EntryPointAccess::Groups(vec![Group::new("constructor")]),
-        EntryPointType::Contract,
-    ));
-    entry_points.add_entry_point(EntryPoint::new(
-        "set_settings",
-        vec![
-            Parameter::new("wise_token", Key::cl_type()),
-            Parameter::new("pair_wise", Key::cl_type()),
-            Parameter::new("pair_scspr", Key::cl_type()),
-            Parameter::new("synthetic_cspr", Key::cl_type()),
-        ],
-        <()>::cl_type(),
-        EntryPointAccess::Public,
-        EntryPointType::Contract,
-    ));
-    entry_points.add_entry_point(EntryPoint::new(
-        "renounce_keeper",
-        vec![],
-        <()>::cl_type(),
-        EntryPointAccess::Public,
-        EntryPointType::Contract,
-    ));
-    entry_points.add_entry_point(EntryPoint::new(
-        "reserve_wise",
-        vec![
-            Parameter::new("investment_mode", u8::cl_type()),
-            Parameter::new("msg_value", U256::cl_type()),
-            Parameter::new("caller_purse", URef::cl_type()),
-        ],
-        <()>::cl_type(),
-        EntryPointAccess::Public,
-        EntryPointType::Contract,
-    ));
-    entry_points.add_entry_point(EntryPoint::new(
-        "reserve_wise_with_token",
-        vec![
-            Parameter::new("token_address", Key::cl_type()),
-            Parameter::new("token_amount", U256::cl_type()),
-            Parameter::new("investment_mode", u8::cl_type()),
-            Parameter::new("caller_purse", URef::cl_type()),
-        ],
-        <()>::cl_type(),
+/// @notice Number of investment days of the sale
+#[no_mangle]
+fn investment_days() {
+    runtime::ret(CLValue::from_t(data::investment_days()).unwrap_or_revert());
+}
+
+/// @notice Maximum amount of WISE tokens that can be reserved
+#[no_mangle]
+fn max_supply() {
+    runtime::ret(CLValue::from_t(data::max_supply()).unwrap_or_revert());
+}
+
+/// @notice Maximum amount of CSPR that can be invested
+#[no_mangle]
+fn max_invest() {
+    runtime::ret(CLValue::from_t(data::max_invest()).unwrap_or_revert());
+}
+
+/// @notice Price of one WISE token in CSPR motes
+#[no_mangle]
+fn token_cost() {
+    runtime::ret(CLValue::from_t(data::token_cost()).unwrap_or_revert());
+}
+
+/// @notice Maximum cash back paid out in total
+#[no_mangle]
+fn refund_cap() {
+    runtime::ret(CLValue::from_t(data::refund_cap()).unwrap_or_revert());
+}
+
+/// @notice Days after forward_liquidity before vested tokens can be claimed
+#[no_mangle]
+fn vesting_cliff_days() {
+    runtime::ret(CLValue::from_t(data::vesting_cliff_days()).unwrap_or_revert());
+}
+
+/// @notice Days over which tokens are released after forward_liquidity, zero if disabled
+#[no_mangle]
+fn vesting_days() {
+    runtime::ret(CLValue::from_t(data::vesting_days()).unwrap_or_revert());
+}
+
+/// @notice Sale mode, 0 for fixed price, 1 for daily pro rata allocation and 2 for bonding curve
+#[no_mangle]
+fn sale_mode() {
+    runtime::ret(CLValue::from_t(data::sale_mode()).unwrap_or_revert());
+}
+
+/// @notice Minimum CSPR the sale has to raise
+#[no_mangle]
+fn soft_cap() {
+    runtime::ret(CLValue::from_t(data::soft_cap()).unwrap_or_revert());
+}
+
+/// @notice Shows if the investment days ended below the soft cap
+#[no_mangle]
+fn sale_failed() {
+    let ret: bool = LiquidityTransformer::default().sale_failed();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Records a failed sale and emits SaleFailed
+/// @dev can be called by anyone once the investment days ended below the soft cap,
+///     only once, request_refund marks it too if nobody did before
+#[no_mangle]
+fn mark_sale_failed() {
+    LiquidityTransformer::default().mark_sale_failed();
+}
+
+/// @notice Stakeable day on which MAX_SUPPLY was reached, zero while the sale is open
+/// @dev forward_liquidity can be called from the day after it
+#[no_mangle]
+fn close_day() {
+    let ret: u64 = LiquidityTransformer::default().close_day();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Merkle root of the reservation allowlist
+#[no_mangle]
+fn allowlist_root() {
+    runtime::ret(CLValue::from_t(data::allowlist_root()).unwrap_or_revert());
+}
+
+/// @notice Shows if reservations need a signed KYC approval
+#[no_mangle]
+fn compliance_mode() {
+    runtime::ret(CLValue::from_t(data::compliance_mode()).unwrap_or_revert());
+}
+
+/// @notice Public key that signs KYC approvals
+#[no_mangle]
+fn kyc_signer() {
+    runtime::ret(CLValue::from_t(data::kyc_signer()).unwrap_or_revert());
+}
+
+/// @notice Message the KYC signer has to sign to approve a reservation
+/// @dev the signature is made over the UTF-8 bytes of the returned string
+/// @param caller address sending the reservation
+/// @param investor_address address credited with the reservation
+/// @param max_amount maximum investor balance including the reservation
+/// @param expiry blocktime after which the approval is rejected
+/// @param nonce value used once per investor
+/// @return digest hex encoded keccak256 of the approval
+#[no_mangle]
+fn kyc_digest() {
+    let caller: Key = runtime::get_named_arg("caller");
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+    let max_amount: U256 = runtime::get_named_arg("max_amount");
+    let expiry: u64 = runtime::get_named_arg("expiry");
+    let nonce: U256 = runtime::get_named_arg("nonce");
+
+    let ret: String = LiquidityTransformer::default().kyc_digest(
+        caller,
+        investor_address,
+        max_amount,
+        expiry,
+        nonce,
+    );
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows if an investor already used a KYC nonce
+/// @param investor_address address of the investor
+/// @param nonce nonce of the approval
+#[no_mangle]
+fn kyc_nonce_used() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+    let nonce: U256 = runtime::get_named_arg("nonce");
+
+    let ret: bool = LiquidityTransformer::default().kyc_nonce_used(investor_address, nonce);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Minimum total, maximum total and daily cap per investor
+#[no_mangle]
+fn investor_limits() {
+    runtime::ret(CLValue::from_t(data::investor_limits()).unwrap_or_revert());
+}
+
+/// @notice Shows how much CSPR an investor can still reserve today
+/// @param investor_address address of the investor
+/// @return allowance remaining CSPR, U256 max when no limit applies
+#[no_mangle]
+fn remaining_allowance() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().remaining_allowance(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Percentage of the withdrawn CSPR kept by withdraw_reservation
+#[no_mangle]
+fn withdraw_penalty() {
+    runtime::ret(CLValue::from_t(data::withdraw_penalty()).unwrap_or_revert());
+}
+
+/// @notice Shows if the keeper cancelled the sale
+#[no_mangle]
+fn sale_cancelled() {
+    let ret: bool = LiquidityTransformer::default().sale_cancelled();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows cash back paid to an investor, deducted from its refund
+/// @param investor_address address of the investor
+#[no_mangle]
+fn cash_back_paid() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().cash_back_paid(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows if an investor was refunded or withdrew its whole reservation
+/// @param investor_address address of the investor
+#[no_mangle]
+fn refunded() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: bool = LiquidityTransformer::default().refunded(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Bonding curve type, factor and step
+#[no_mangle]
+fn bonding_curve() {
+    runtime::ret(CLValue::from_t(data::bonding_curve()).unwrap_or_revert());
+}
+
+/// @notice Shows the allocation a reservation would get right now
+/// @dev in daily sale mode the share of the current day if no one else joins
+/// @param amount CSPR to be reserved
+/// @return token_amount tokens purchased and return_amount CSPR refunded above MAX_SUPPLY
+#[no_mangle]
+fn quote_tokens() {
+    let amount: U256 = runtime::get_named_arg("amount");
+
+    let ret: (U256, U256) = LiquidityTransformer::default().quote_tokens(amount);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows amount of CSPR contributed on an investment day
+/// @param day investment day
+#[no_mangle]
+fn daily_totals() {
+    let day: u64 = runtime::get_named_arg("day");
+
+    let ret: U256 = LiquidityTransformer::default().daily_totals(day);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows what an investor recorded on an investment day
+/// @param investor_address address of the investor
+/// @param day investment day
+/// @return cspr contributed, bonus weighted and referral weighted CSPR of the day
+#[no_mangle]
+fn investor_daily_balance() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+    let day: u64 = runtime::get_named_arg("day");
+
+    let ret: (U256, U256, U256) =
+        LiquidityTransformer::default().investor_daily_balance(investor_address, day);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Allowed deviation in percent between the pool price and the sale price
+#[no_mangle]
+fn price_tolerance() {
+    runtime::ret(CLValue::from_t(data::price_tolerance()).unwrap_or_revert());
+}
+
+/// @notice Destination and recipient of the LP tokens minted by forward_liquidity
+#[no_mangle]
+fn lp_destination() {
+    runtime::ret(
+        CLValue::from_t((data::lp_destination(), data::lp_recipient())).unwrap_or_revert(),
+    );
+}
+
+/// @notice Shows amount of tokens an investor can claim right now
+/// @param investor_address address of the investor
+/// @return claimable vested tokens not yet minted to the investor
+#[no_mangle]
+fn claimable() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().claimable(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Shows amount of LP tokens an investor can claim
+/// @param investor_address address of the investor
+/// @return claimable_lp share of the LP tokens proportional to the investor balance
+#[no_mangle]
+fn claimable_lp() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+
+    let ret: U256 = LiquidityTransformer::default().claimable_lp(investor_address);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice LP tokens kept for investors and LP tokens already claimed
+#[no_mangle]
+fn lp_totals() {
+    let ret: (U256, U256) = LiquidityTransformer::default().lp_totals();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+fn get_entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        "constructor",
+        vec![
+            Parameter::new("wise", Key::cl_type()),
+            Parameter::new("scspr", Key::cl_type()),
+            Parameter::new("pair_wise", Key::cl_type()),
+            Parameter::new("pair_scspr", Key::cl_type()),
+            Parameter::new("uniswap_router", Key::cl_type()),
+            Parameter::new("wcspr", Key::cl_type()),
+            Parameter::new("package_hash", ContractPackageHash::cl_type()),
+            Parameter::new("contract_hash", ContractHash::cl_type()),
+            Parameter::new("purse", URef::cl_type()),
+            Parameter::new("investment_days", u8::cl_type()),
+            Parameter::new("max_supply", U256::cl_type()),
+            Parameter::new("max_invest", U256::cl_type()),
+            Parameter::new("token_cost", U256::cl_type()),
+            Parameter::new("refund_cap", U256::cl_type()),
+            Parameter::new("vesting_cliff_days", u64::cl_type()),
+            Parameter::new("vesting_days", u64::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Groups(vec![Group::new("constructor")]),
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "upgrade",
+        vec![
+            Parameter::new("contract_hash", ContractHash::cl_type()),
+            Parameter::new("investment_days", u8::cl_type()),
+            Parameter::new("max_supply", U256::cl_type()),
+            Parameter::new("max_invest", U256::cl_type()),
+            Parameter::new("token_cost", U256::cl_type()),
+            Parameter::new("refund_cap", U256::cl_type()),
+            Parameter::new("vesting_cliff_days", u64::cl_type()),
+            Parameter::new("vesting_days", u64::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Groups(vec![Group::new("constructor")]),
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_settings",
+        vec![
+            Parameter::new("wise_token", Key::cl_type()),
+            Parameter::new("pair_wise", Key::cl_type()),
+            Parameter::new("pair_scspr", Key::cl_type()),
+            Parameter::new("synthetic_cspr", Key::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_swap_route",
+        vec![
+            Parameter::new("token_address", Key::cl_type()),
+            Parameter::new(
+                "intermediate_tokens",
+                CLType::List(Box::new(Key::cl_type())),
+            ),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_accepted_token",
+        vec![
+            Parameter::new("token_address", Key::cl_type()),
+            Parameter::new("accepted", bool::cl_type()),
+            Parameter::new("min_amount", U256::cl_type()),
+            Parameter::new("max_amount", U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "accepted_token",
+        vec![Parameter::new("token_address", Key::cl_type())],
+        <((bool, U256, U256), U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_referral_bonus",
+        vec![Parameter::new("referral_bonus", U256::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "referral_bonus",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_investment_mode",
+        vec![
+            Parameter::new("investment_mode", u8::cl_type()),
+            Parameter::new("cash_back_rate", U256::cl_type()),
+            Parameter::new("token_bonus", U256::cl_type()),
+            Parameter::new("lock_days", u64::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_sale_mode",
+        vec![Parameter::new("sale_mode", u8::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_bonding_curve",
+        vec![
+            Parameter::new("curve_type", u8::cl_type()),
+            Parameter::new("factor", U256::cl_type()),
+            Parameter::new("step", U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_soft_cap",
+        vec![Parameter::new("soft_cap", U256::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_allowlist_root",
+        vec![Parameter::new(
+            "allowlist_root",
+            Option::<String>::cl_type(),
+        )],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_kyc_signer",
+        vec![Parameter::new("kyc_signer", PublicKey::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_compliance_mode",
+        vec![Parameter::new("compliance_mode", bool::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_investor_limits",
+        vec![
+            Parameter::new("min_investment", U256::cl_type()),
+            Parameter::new("max_investment", U256::cl_type()),
+            Parameter::new("daily_cap", U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_withdraw_penalty",
+        vec![Parameter::new("withdraw_penalty", U256::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_price_tolerance",
+        vec![Parameter::new("price_tolerance", U256::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_lp_destination",
+        vec![
+            Parameter::new("lp_destination", u8::cl_type()),
+            Parameter::new("lp_recipient", Key::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investment_mode",
+        vec![Parameter::new("investment_mode", u8::cl_type())],
+        <(U256, U256, u64)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investment_mode_totals",
+        vec![Parameter::new("investment_mode", u8::cl_type())],
+        <((U256, U256), (U256, U256))>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "cancel_sale",
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "renounce_keeper",
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "reserve_wise",
+        vec![
+            Parameter::new("investment_mode", u8::cl_type()),
+            Parameter::new("msg_value", U256::cl_type()),
+            Parameter::new("referral_address", CLType::Option(Box::new(Key::cl_type()))),
+            Parameter::new("beneficiary", CLType::Option(Box::new(Key::cl_type()))),
+            Parameter::new("kyc_approval", Option::<KycApproval>::cl_type()),
+            Parameter::new("allowlist_proof", Option::<AllowlistProof>::cl_type()),
+            Parameter::new("caller_purse", URef::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "reserve_wise_with_token",
+        vec![
+            Parameter::new("token_address", Key::cl_type()),
+            Parameter::new("token_amount", U256::cl_type()),
+            Parameter::new("investment_mode", u8::cl_type()),
+            Parameter::new("min_cspr_out", U256::cl_type()),
+            Parameter::new("deadline", U256::cl_type()),
+            Parameter::new(
+                "path",
+                CLType::Option(Box::new(CLType::List(Box::new(Key::cl_type())))),
+            ),
+            Parameter::new("referral_address", CLType::Option(Box::new(Key::cl_type()))),
+            Parameter::new("beneficiary", CLType::Option(Box::new(Key::cl_type()))),
+            Parameter::new("kyc_approval", Option::<KycApproval>::cl_type()),
+            Parameter::new("allowlist_proof", Option::<AllowlistProof>::cl_type()),
+            Parameter::new("caller_purse", URef::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "forward_liquidity",
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_my_tokens",
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_my_tokens_and_stake",
+        vec![
+            Parameter::new("lock_days", u64::cl_type()),
+            Parameter::new("referrer", Key::cl_type()),
+        ],
+        CLType::List(Box::new(u32::cl_type())),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "claim_lp",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "settle_investor",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "payout_investor_address",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "payout_investors_batch",
+        vec![
+            Parameter::new("start", CLType::Option(Box::new(U256::cl_type()))),
+            Parameter::new("count", U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "payout_cursor",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "prepare_path",
+        vec![Parameter::new("token_address", Key::cl_type())],
+        CLType::List(Box::new(Key::cl_type())),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "current_stakeable_day",
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "request_refund",
+        vec![Parameter::new("caller_purse", URef::cl_type())],
+        CLType::Tuple2([Box::new(CLType::U256), Box::new(CLType::U256)]),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "withdraw_reservation",
+        vec![
+            Parameter::new("amount", U256::cl_type()),
+            Parameter::new("caller_purse", URef::cl_type()),
+        ],
+        U256::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        "forward_liquidity",
-        vec![],
-        <()>::cl_type(),
+        "sweep_withdraw_penalties",
+        vec![Parameter::new("treasury_purse", URef::cl_type())],
+        U256::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        "get_my_tokens",
+        "sweep_dust",
+        vec![Parameter::new("recipient", Key::cl_type())],
+        CLType::Tuple2([Box::new(CLType::U256), Box::new(CLType::U256)]),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "dust",
         vec![],
-        <()>::cl_type(),
+        CLType::Tuple2([Box::new(CLType::U256), Box::new(CLType::U256)]),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        "payout_investor_address",
+        "bonus_tokens",
         vec![Parameter::new("investor_address", Key::cl_type())],
         U256::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        "prepare_path",
-        vec![Parameter::new("token_address", Key::cl_type())],
-        CLType::List(Box::new(Key::cl_type())),
+        "referral_tokens",
+        vec![Parameter::new("referral_address", Key::cl_type())],
+        U256::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        "current_stakeable_day",
+        "payout_stakes",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        CLType::List(Box::new(CLType::Tuple2([
+            Box::new(CLType::List(Box::new(u32::cl_type()))),
+            Box::new(CLType::U256),
+        ]))),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investor_balance",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "purchased_tokens",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "globals",
         vec![],
-        u64::cl_type(),
+        <((U256, U256), (U256, U256), bool)>::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        "request_refund",
-        vec![Parameter::new("caller_purse", URef::cl_type())],
-        CLType::Tuple2([Box::new(CLType::U256), Box::new(CLType::U256)]),
+        "check_invariants",
+        vec![
+            Parameter::new("offset", U256::cl_type()),
+            Parameter::new("limit", U256::cl_type()),
+            Parameter::new("totals", <((U256, U256, U256), (U256, U256))>::cl_type()),
+        ],
+        <(((U256, U256, U256), (U256, U256)), Option<bool>)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "refunded",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investor_counts",
+        vec![],
+        <(U256, U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "referrer_count",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "migrate_investor_registry",
+        vec![Parameter::new("count", U256::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "unique_investors",
+        vec![
+            Parameter::new("offset", U256::cl_type()),
+            Parameter::new("limit", U256::cl_type()),
+        ],
+        CLType::List(Box::new(Key::cl_type())),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
@@ -325,6 +1522,235 @@ fn get_entry_points() -> EntryPoints {
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investment_days",
+        vec![],
+        u8::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "max_supply",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "max_invest",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "token_cost",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "refund_cap",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "vesting_cliff_days",
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "vesting_days",
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "sale_mode",
+        vec![],
+        u8::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "soft_cap",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "sale_failed",
+        vec![],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "mark_sale_failed",
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "allowlist_root",
+        vec![],
+        Option::<String>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "compliance_mode",
+        vec![],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "kyc_signer",
+        vec![],
+        Option::<PublicKey>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "kyc_digest",
+        vec![
+            Parameter::new("caller", Key::cl_type()),
+            Parameter::new("investor_address", Key::cl_type()),
+            Parameter::new("max_amount", U256::cl_type()),
+            Parameter::new("expiry", u64::cl_type()),
+            Parameter::new("nonce", U256::cl_type()),
+        ],
+        String::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "kyc_nonce_used",
+        vec![
+            Parameter::new("investor_address", Key::cl_type()),
+            Parameter::new("nonce", U256::cl_type()),
+        ],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investor_limits",
+        vec![],
+        <(U256, U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "remaining_allowance",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "withdraw_penalty",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "close_day",
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "sale_cancelled",
+        vec![],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "cash_back_paid",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "bonding_curve",
+        vec![],
+        <(u8, U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "quote_tokens",
+        vec![Parameter::new("amount", U256::cl_type())],
+        <(U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "daily_totals",
+        vec![Parameter::new("day", u64::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "investor_daily_balance",
+        vec![
+            Parameter::new("investor_address", Key::cl_type()),
+            Parameter::new("day", u64::cl_type()),
+        ],
+        <(U256, U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "price_tolerance",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "lp_destination",
+        vec![],
+        <(u8, Key)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "claimable",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "claimable_lp",
+        vec![Parameter::new("investor_address", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "lp_totals",
+        vec![],
+        <(U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
     entry_points
 }
 
@@ -356,6 +1782,13 @@ pub extern "C" fn call() {
         let pair_scspr: Key = runtime::get_named_arg("pair_scspr");
         let uniswap_router: Key = runtime::get_named_arg("uniswap_router");
         let wcspr: Key = runtime::get_named_arg("wcspr");
+        let investment_days: u8 = runtime::get_named_arg("investment_days");
+        let max_supply: U256 = runtime::get_named_arg("max_supply");
+        let max_invest: U256 = runtime::get_named_arg("max_invest");
+        let token_cost: U256 = runtime::get_named_arg("token_cost");
+        let refund_cap: U256 = runtime::get_named_arg("refund_cap");
+        let vesting_cliff_days: u64 = runtime::get_named_arg("vesting_cliff_days");
+        let vesting_days: u64 = runtime::get_named_arg("vesting_days");
         let constructor_args = runtime_args! {
             "wise" => wise,
             "scspr" => scspr,
@@ -365,7 +1798,14 @@ pub extern "C" fn call() {
             "wcspr" => wcspr,
             "package_hash" => package_hash,
             "contract_hash" => contract_hash,
-            "purse" => purse
+            "purse" => purse,
+            "investment_days" => investment_days,
+            "max_supply" => max_supply,
+            "max_invest" => max_invest,
+            "token_cost" => token_cost,
+            "refund_cap" => refund_cap,
+            "vesting_cliff_days" => vesting_cliff_days,
+            "vesting_days" => vesting_days
         };
 
         // Add the constructor group to the package hash with a single URef.
@@ -415,10 +1855,34 @@ pub extern "C" fn call() {
                 .into_hash()
                 .unwrap()
                 .into();
-        // create new version and install it
+        // create new version and install it, the execution engine copies the named keys of
+        // the current version into the new one, so every dictionary and stored value carries
+        // over and only keys added by this version need to be passed here
         let (contract_hash, _): (ContractHash, _) =
             storage::add_contract_version(package_hash, get_entry_points(), Default::default());
 
+        // Sale parameters are only stored when upgrading from a version that had them hardcoded
+        let upgrade_args = runtime_args! {
+            "contract_hash" => contract_hash,
+            "investment_days" => runtime::get_named_arg::<u8>("investment_days"),
+            "max_supply" => runtime::get_named_arg::<U256>("max_supply"),
+            "max_invest" => runtime::get_named_arg::<U256>("max_invest"),
+            "token_cost" => runtime::get_named_arg::<U256>("token_cost"),
+            "refund_cap" => runtime::get_named_arg::<U256>("refund_cap"),
+            "vesting_cliff_days" => runtime::get_named_arg::<u64>("vesting_cliff_days"),
+            "vesting_days" => runtime::get_named_arg::<u64>("vesting_days")
+        };
+
+        // Give the installer a temporary URef of the constructor group to run the upgrade
+        let upgrade_access: URef =
+            storage::provision_contract_user_group_uref(package_hash, "constructor")
+                .unwrap_or_revert();
+        let _: () = runtime::call_versioned_contract(package_hash, None, "upgrade", upgrade_args);
+        let mut urefs = BTreeSet::new();
+        urefs.insert(upgrade_access);
+        storage::remove_contract_user_group_urefs(package_hash, "constructor", urefs)
+            .unwrap_or_revert();
+
         // update contract hash
         runtime::put_key(
             &format!("{}_contract_hash", contract_name),
@@ -0,0 +1,37 @@
+//! Prints the root of an allowlist CSV followed by one `<address>,<cap>,<proof>` line per
+//! entry, where the proof hashes are separated by `;`.
+
+use std::{env, fs::File, io::BufReader, process};
+
+use liquidity_transformer_allowlist::MerkleTree;
+
+fn main() {
+    let path: String = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: allowlist <entries.csv>");
+            process::exit(2);
+        }
+    };
+    let tree: MerkleTree = match File::open(&path)
+        .map_err(Into::into)
+        .and_then(|file| MerkleTree::from_csv(BufReader::new(file)))
+    {
+        Ok(tree) => tree,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            process::exit(1);
+        }
+    };
+
+    println!("root,{}", tree.root_hex());
+    for entry in tree.entries() {
+        let (cap, proof) = tree.proof_hex(&entry.address).unwrap();
+        println!(
+            "{},{},{}",
+            entry.address.to_formatted_string(),
+            cap,
+            proof.join(";")
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! CSV parsing and Merkle tree building, only available with the `std` feature.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufRead},
+};
+
+use casper_types::{Key, U256};
+
+use crate::hash::{leaf_hash, Hash};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    MissingColumn { line: usize },
+    ExtraColumn { line: usize },
+    InvalidAddress { line: usize, value: String },
+    InvalidCap { line: usize, value: String },
+    DuplicateAddress { line: usize, value: String },
+    Empty,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "failed to read allowlist: {}", error),
+            Error::MissingColumn { line } | Error::ExtraColumn { line } => {
+                write!(f, "line {}: expected <address>,<cap>", line)
+            }
+            Error::InvalidAddress { line, value } => {
+                write!(f, "line {}: invalid address {}", line, value)
+            }
+            Error::InvalidCap { line, value } => write!(f, "line {}: invalid cap {}", line, value),
+            Error::DuplicateAddress { line, value } => {
+                write!(f, "line {}: duplicate address {}", line, value)
+            }
+            Error::Empty => write!(f, "allowlist has no entries"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllowlistEntry {
+    pub address: Key,
+    pub cap: U256,
+}
+
+/// Reads `<formatted address>,<cap>` lines, caps are decimal motes.
+/// Blank lines, `#` comments and an `address,cap` header are skipped.
+pub fn parse_csv<R: BufRead>(reader: R) -> Result<Vec<AllowlistEntry>, Error> {
+    let mut entries: Vec<AllowlistEntry> = Vec::new();
+    let mut seen: BTreeMap<Key, usize> = BTreeMap::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line_number: usize = index + 1;
+        let line: String = line?;
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut columns = line.split(',').map(str::trim);
+        let (address, cap) = match (columns.next(), columns.next()) {
+            (Some(address), Some(cap)) => (address, cap),
+            _ => return Err(Error::MissingColumn { line: line_number }),
+        };
+        if columns.next().is_some() {
+            return Err(Error::ExtraColumn { line: line_number });
+        }
+        if address == "address" && cap == "cap" {
+            continue;
+        }
+        let address: Key = Key::from_formatted_str(address).map_err(|_| Error::InvalidAddress {
+            line: line_number,
+            value: address.to_string(),
+        })?;
+        let cap: U256 = U256::from_dec_str(cap).map_err(|_| Error::InvalidCap {
+            line: line_number,
+            value: cap.to_string(),
+        })?;
+        if seen.insert(address, line_number).is_some() {
+            return Err(Error::DuplicateAddress {
+                line: line_number,
+                value: address.to_formatted_string(),
+            });
+        }
+        entries.push(AllowlistEntry { address, cap });
+    }
+    Ok(entries)
+}
+
+pub struct MerkleTree {
+    entries: Vec<AllowlistEntry>,
+    positions: BTreeMap<Key, usize>,
+    // levels[0] holds the leaves, the last level holds the root
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(entries: Vec<AllowlistEntry>) -> Result<MerkleTree, Error> {
+        if entries.is_empty() {
+            return Err(Error::Empty);
+        }
+        let mut positions: BTreeMap<Key, usize> = BTreeMap::new();
+        for (position, entry) in entries.iter().enumerate() {
+            if positions.insert(entry.address, position).is_some() {
+                return Err(Error::DuplicateAddress {
+                    line: position + 1,
+                    value: entry.address.to_formatted_string(),
+                });
+            }
+        }
+
+        let leaves: Vec<Hash> = entries
+            .iter()
+            .map(|entry| leaf_hash(&entry.address, entry.cap))
+            .collect();
+        let mut levels: Vec<Vec<Hash>> = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let level: Vec<Hash> = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node_hash(*a, *b),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+        }
+        Ok(MerkleTree {
+            entries,
+            positions,
+            levels,
+        })
+    }
+
+    pub fn from_csv<R: BufRead>(reader: R) -> Result<MerkleTree, Error> {
+        MerkleTree::new(parse_csv(reader)?)
+    }
+
+    pub fn entries(&self) -> &[AllowlistEntry] {
+        &self.entries
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Value for the `allowlist_root` argument of `set_allowlist_root`
+    pub fn root_hex(&self) -> String {
+        hex::encode(self.root())
+    }
+
+    pub fn proof(&self, address: &Key) -> Option<(U256, Vec<Hash>)> {
+        let mut position: usize = *self.positions.get(address)?;
+        let mut proof: Vec<Hash> = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling: usize = position ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            position /= 2;
+        }
+        Some((self.entries[self.positions[address]].cap, proof))
+    }
+
+    /// Value for the `allowlist_proof` argument of `reserve_wise` and `reserve_wise_with_token`
+    pub fn proof_hex(&self, address: &Key) -> Option<(U256, Vec<String>)> {
+        let (cap, proof): (U256, Vec<Hash>) = self.proof(address)?;
+        Some((cap, proof.iter().map(hex::encode).collect()))
+    }
+}
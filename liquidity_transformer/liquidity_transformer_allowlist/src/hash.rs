@@ -0,0 +1,30 @@
+//! Allowlist hashing shared by the host side builder and the liquidity transformer contract.
+
+use alloc::{format, string::String};
+
+use casper_types::{Key, U256};
+
+pub type Hash = [u8; 32];
+
+/// `keccak256("<formatted address>_<cap>")`, the string is always longer than the 64 bytes
+/// hashed for inner nodes so a leaf can not pass for a node
+pub fn leaf_hash(address: &Key, cap: U256) -> Hash {
+    let leaf: String = format!("{}_{}", address.to_formatted_string(), cap);
+    renvm_sig::keccak256(leaf.as_bytes())
+}
+
+/// Hashes the sorted pair so proofs do not need left/right flags
+pub fn node_hash(a: Hash, b: Hash) -> Hash {
+    let (first, second): (Hash, Hash) = if a <= b { (a, b) } else { (b, a) };
+    let mut pair: [u8; 64] = [0; 64];
+    pair[..32].copy_from_slice(&first);
+    pair[32..].copy_from_slice(&second);
+    renvm_sig::keccak256(&pair)
+}
+
+pub fn verify(root: Hash, address: &Key, cap: U256, proof: &[Hash]) -> bool {
+    let node: Hash = proof.iter().fold(leaf_hash(address, cap), |node, sibling| {
+        node_hash(node, *sibling)
+    });
+    node == root
+}
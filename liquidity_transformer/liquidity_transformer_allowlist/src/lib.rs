@@ -0,0 +1,157 @@
+//! Host side builder for the liquidity transformer reservation allowlist.
+//!
+//! Leaves are `keccak256("<formatted address>_<cap>")` and inner nodes hash the sorted pair of
+//! their children. The contract checks proofs in `reserve_wise` with the same `hash` module,
+//! built without the default `std` feature. A node without a sibling is promoted to the next
+//! level unchanged, so its proof is one hash shorter.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod builder;
+mod hash;
+
+#[cfg(feature = "std")]
+pub use builder::{parse_csv, AllowlistEntry, Error, MerkleTree};
+pub use hash::{leaf_hash, node_hash, verify, Hash};
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use casper_types::{account::AccountHash, Key, U256};
+
+    fn address(byte: u8) -> Key {
+        Key::Account(AccountHash::new([byte; 32]))
+    }
+
+    fn entries(count: u8) -> Vec<AllowlistEntry> {
+        (1..=count)
+            .map(|byte| AllowlistEntry {
+                address: address(byte),
+                cap: U256::from(byte as u64 * 1_000),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_csv_skips_header_comments_and_blank_lines() {
+        let csv: String = format!(
+            "address,cap\n# first investor\n\n{},1000\n {} , 2000 \n",
+            address(1).to_formatted_string(),
+            address(2).to_formatted_string()
+        );
+        let parsed: Vec<AllowlistEntry> = parse_csv(csv.as_bytes()).unwrap();
+        assert_eq!(parsed, entries(2));
+    }
+
+    #[test]
+    fn parse_csv_rejects_duplicate_address() {
+        let csv: String = format!("{0},1000\n{0},2000\n", address(1).to_formatted_string());
+        match parse_csv(csv.as_bytes()) {
+            Err(Error::DuplicateAddress { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a duplicate address, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_csv_rejects_bad_cap() {
+        let csv: String = format!("{},-5\n", address(1).to_formatted_string());
+        match parse_csv(csv.as_bytes()) {
+            Err(Error::InvalidCap { line, value }) => {
+                assert_eq!(line, 1);
+                assert_eq!(value, "-5");
+            }
+            other => panic!("expected an invalid cap, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_csv_rejects_missing_column() {
+        let csv: String = format!("{}\n", address(1).to_formatted_string());
+        assert!(matches!(
+            parse_csv(csv.as_bytes()),
+            Err(Error::MissingColumn { line: 1 })
+        ));
+    }
+
+    #[test]
+    fn parse_csv_rejects_extra_column() {
+        let csv: String = format!("{},1000,2000\n", address(1).to_formatted_string());
+        assert!(matches!(
+            parse_csv(csv.as_bytes()),
+            Err(Error::ExtraColumn { line: 1 })
+        ));
+    }
+
+    #[test]
+    fn empty_allowlist_is_rejected() {
+        assert!(matches!(MerkleTree::new(Vec::new()), Err(Error::Empty)));
+    }
+
+    #[test]
+    fn single_entry_root_is_its_leaf() {
+        let tree: MerkleTree = MerkleTree::new(entries(1)).unwrap();
+        assert_eq!(tree.root(), leaf_hash(&address(1), U256::from(1_000)));
+        assert_eq!(
+            tree.proof(&address(1)),
+            Some((U256::from(1_000), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn proofs_verify_for_every_tree_size() {
+        // Odd sizes promote the last node, so its proof is shorter than the others
+        for count in 1..=9 {
+            let tree: MerkleTree = MerkleTree::new(entries(count)).unwrap();
+            for entry in tree.entries() {
+                let (cap, proof): (U256, Vec<Hash>) = tree.proof(&entry.address).unwrap();
+                assert_eq!(cap, entry.cap);
+                assert!(
+                    verify(tree.root(), &entry.address, cap, &proof),
+                    "proof of {} in a tree of {} does not verify",
+                    entry.address.to_formatted_string(),
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn odd_tree_promotes_last_leaf() {
+        let tree: MerkleTree = MerkleTree::new(entries(3)).unwrap();
+        let (_, proof): (U256, Vec<Hash>) = tree.proof(&address(3)).unwrap();
+        assert_eq!(
+            proof,
+            vec![node_hash(
+                leaf_hash(&address(1), U256::from(1_000)),
+                leaf_hash(&address(2), U256::from(2_000))
+            )]
+        );
+        let (_, proof): (U256, Vec<Hash>) = tree.proof(&address(1)).unwrap();
+        assert_eq!(proof.len(), 2);
+    }
+
+    #[test]
+    fn verify_rejects_other_cap_or_address() {
+        let tree: MerkleTree = MerkleTree::new(entries(4)).unwrap();
+        let (cap, proof): (U256, Vec<Hash>) = tree.proof(&address(2)).unwrap();
+        assert!(!verify(tree.root(), &address(2), cap + U256::one(), &proof));
+        assert!(!verify(tree.root(), &address(3), cap, &proof));
+        assert_eq!(tree.proof(&address(5)), None);
+    }
+
+    #[test]
+    fn proof_hex_matches_proof() {
+        let tree: MerkleTree = MerkleTree::new(entries(5)).unwrap();
+        let (cap, proof): (U256, Vec<Hash>) = tree.proof(&address(4)).unwrap();
+        let (hex_cap, hex_proof): (U256, Vec<String>) = tree.proof_hex(&address(4)).unwrap();
+        assert_eq!(hex_cap, cap);
+        assert_eq!(
+            hex_proof,
+            proof.iter().map(hex::encode).collect::<Vec<String>>()
+        );
+        assert_eq!(tree.root_hex(), hex::encode(tree.root()));
+    }
+}
@@ -1,8 +1,12 @@
-use alloc::string::ToString;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use casper_contract::{contract_api::runtime, unwrap_or_revert::UnwrapOrRevert};
 use casper_types::{
     bytesrepr::{FromBytes, ToBytes},
-    ApiError, CLTyped, Key, URef, U256,
+    ApiError, CLTyped, Key, PublicKey, URef, U256,
 };
 use casperlabs_contract_utils::{get_key, key_to_str, set_key, Dict};
 
@@ -14,15 +18,79 @@ pub const UNISWAP_ROUTER_PACKAGE: &str = "uniswap_router_package";
 pub const WCSPR: &str = "wcspr";
 pub const SCSPR: &str = "scspr";
 
-pub const INVESTMENT_DAYS: u8 = 15;
-pub const MAX_SUPPLY: u128 = 264_000_000_000_000_000; // 264000000E9;
-pub const MAX_INVEST: u128 = 200_000_000_000_000; // 200000E9;
-pub const TOKEN_COST: u128 = MAX_INVEST / (MAX_SUPPLY / 1_000_000_000); // MAX_INVEST / (MAX_SUPPLY / 1E9);
-pub const REFUND_CAP: u128 = 100_000_000_000; // 100E9;
+pub const INVESTMENT_DAYS: &str = "investment_days";
+pub const MAX_SUPPLY: &str = "max_supply";
+pub const MAX_INVEST: &str = "max_invest";
+pub const TOKEN_COST: &str = "token_cost";
+pub const REFUND_CAP: &str = "refund_cap";
+pub const REFERRAL_BONUS: &str = "referral_bonus";
+pub const VESTING_CLIFF_DAYS: &str = "vesting_cliff_days";
+pub const VESTING_DAYS: &str = "vesting_days";
+pub const LP_DESTINATION: &str = "lp_destination";
+pub const LP_RECIPIENT: &str = "lp_recipient";
+pub const PRICE_TOLERANCE: &str = "price_tolerance";
+pub const SALE_MODE: &str = "sale_mode";
+pub const BONDING_CURVE: &str = "bonding_curve";
+pub const SOFT_CAP: &str = "soft_cap";
+pub const WITHDRAW_PENALTY: &str = "withdraw_penalty";
+pub const INVESTOR_LIMITS: &str = "investor_limits";
+pub const COMPLIANCE_MODE: &str = "compliance_mode";
+pub const KYC_SIGNER: &str = "kyc_signer";
+pub const ALLOWLIST_ROOT: &str = "allowlist_root";
+
+/// ((max_amount, expiry, nonce), signature) signed off-chain by the KYC signer,
+/// the signature is the hex encoded 64 bytes secp256k1 signature of kyc_digest
+pub type KycApproval = ((U256, u64, U256), String);
+
+/// (cap, proof) for the allowlist leaf of the investor, the proof lists the hex encoded
+/// sibling hashes from the leaf up to the root
+pub type AllowlistProof = (U256, Vec<String>);
+
+pub const SALE_MODE_FIXED_PRICE: u8 = 0;
+pub const SALE_MODE_DAILY: u8 = 1;
+pub const SALE_MODE_BONDING_CURVE: u8 = 2;
+
+pub const CURVE_LINEAR: u8 = 0;
+pub const CURVE_EXPONENTIAL: u8 = 1;
+pub const MAX_CURVE_STEPS: u64 = 100;
+
+pub const LP_DESTINATION_BURN: u8 = 0;
+pub const LP_DESTINATION_TREASURY: u8 = 1;
+pub const LP_DESTINATION_LOCKER: u8 = 2;
+pub const LP_DESTINATION_INVESTORS: u8 = 3;
+
+pub const DEFAULT_PRICE_TOLERANCE: u64 = 1;
+// pair_wise reserves below 1 / DUST_RESERVE_RATIO of the liquidity we add count as dust
+pub const DUST_RESERVE_RATIO: u64 = 1000;
 
 pub const UNIQUE_INVESTORS: &str = "unique_investors";
+pub const INVESTOR_INDEX: &str = "investor_index";
 pub const PURCHASED_TOKENS: &str = "purchased_tokens";
 pub const INVESTOR_BALANCE: &str = "investor_balance";
+pub const SWAP_ROUTES: &str = "swap_routes";
+pub const ACCEPTED_TOKENS: &str = "accepted_tokens";
+pub const ACCEPTED_TOKEN_TOTALS: &str = "accepted_token_totals";
+pub const REFERRAL_TOKENS: &str = "referral_tokens";
+pub const INVESTMENT_MODES: &str = "investment_modes";
+pub const INVESTMENT_MODE_TOTALS: &str = "investment_mode_totals";
+pub const BONUS_TOKENS: &str = "bonus_tokens";
+pub const UNLOCK_DAYS: &str = "unlock_days";
+pub const VESTING_TOTALS: &str = "vesting_totals";
+pub const VESTING_CLAIMED: &str = "vesting_claimed";
+pub const LP_CLAIMED: &str = "lp_claimed";
+pub const DAILY_TOTALS: &str = "daily_totals";
+pub const INVESTOR_DAILY_BALANCE: &str = "investor_daily_balance";
+pub const INVESTOR_DAYS: &str = "investor_days";
+pub const CASH_BACK_PAID: &str = "cash_back_paid";
+pub const REFUNDED: &str = "refunded";
+pub const INVESTOR_DAY_CONTRIBUTIONS: &str = "investor_day_contributions";
+pub const KYC_NONCES: &str = "kyc_nonces";
+pub const INVESTOR_REFERRALS: &str = "investor_referrals";
+pub const INVESTOR_MODE_TOTALS: &str = "investor_mode_totals";
+pub const INVESTOR_TOKEN_AMOUNTS: &str = "investor_token_amounts";
+pub const PAYOUT_STAKES: &str = "payout_stakes";
+pub const REFERRERS: &str = "referrers";
+pub const REFERRER_INDEX: &str = "referrer_index";
 
 pub const SELF_CONTRACT_HASH: &str = "self_contract_hash";
 pub const SELF_PACKAGE_HASH: &str = "self_package_hash";
@@ -37,6 +105,25 @@ pub const INVESTOR_COUNT: &str = "investor_count";
 pub const TOTAL_TRANSFER_TOKENS: &str = "total_transfer_tokens";
 pub const TOTAL_CSPR_CONTRIBUTED: &str = "total_cspr_contributed";
 pub const UNISWAP_SWAPED: &str = "uniswap_swaped";
+pub const PAYOUT_CURSOR: &str = "payout_cursor";
+pub const REFERRAL_TOKENS_TOTAL: &str = "referral_tokens_total";
+pub const LIQUIDITY_DAY: &str = "liquidity_day";
+pub const LP_TOTAL: &str = "lp_total";
+pub const LP_CLAIMED_TOTAL: &str = "lp_claimed_total";
+pub const SALE_FAILED: &str = "sale_failed";
+pub const SALE_CANCELLED: &str = "sale_cancelled";
+pub const CLOSE_DAY: &str = "close_day";
+pub const WITHDRAW_PENALTY_TOTAL: &str = "withdraw_penalty_total";
+pub const REFUNDED_INVESTORS: &str = "refunded_investors";
+pub const REFUNDED_TOTAL: &str = "refunded_total";
+pub const WITHDRAW_PENALTY_SWEPT: &str = "withdraw_penalty_swept";
+pub const UNIQUE_INVESTOR_COUNT: &str = "unique_investor_count";
+pub const ACTIVE_INVESTORS: &str = "active_investors";
+pub const REGISTRY_CURSOR: &str = "registry_cursor";
+pub const REGISTRY_MIGRATED: &str = "registry_migrated";
+pub const REFERRER_COUNT: &str = "referrer_count";
+pub const DUST_WISE: &str = "dust_wise";
+pub const DUST_SCSPR: &str = "dust_scspr";
 
 pub struct Globals {
     dict: Dict,
@@ -110,27 +197,666 @@ impl PurchasedTokens {
     }
 }
 
-pub struct UniqueInvestors {
+pub struct ReferralTokens {
+    dict: Dict,
+}
+
+impl ReferralTokens {
+    pub fn instance() -> ReferralTokens {
+        ReferralTokens {
+            dict: Dict::instance(REFERRAL_TOKENS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(REFERRAL_TOKENS)
+    }
+
+    pub fn get(&self, key: &Key) -> U256 {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: U256) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct InvestmentModes {
+    dict: Dict,
+}
+
+impl InvestmentModes {
+    pub fn instance() -> InvestmentModes {
+        InvestmentModes {
+            dict: Dict::instance(INVESTMENT_MODES),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTMENT_MODES)
+    }
+
+    /// Returns (cash_back_rate, token_bonus, lock_days) of a mode
+    pub fn get(&self, mode: u8) -> (U256, U256, u64) {
+        self.dict.get(mode.to_string().as_str()).unwrap_or_default()
+    }
+
+    pub fn set(&self, mode: u8, value: (U256, U256, u64)) {
+        self.dict.set(mode.to_string().as_str(), value);
+    }
+}
+
+pub struct InvestmentModeTotals {
+    dict: Dict,
+}
+
+impl InvestmentModeTotals {
+    pub fn instance() -> InvestmentModeTotals {
+        InvestmentModeTotals {
+            dict: Dict::instance(INVESTMENT_MODE_TOTALS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTMENT_MODE_TOTALS)
+    }
+
+    /// Returns ((cspr_contributed, purchased_tokens), (cash_back, bonus_tokens)) of a mode
+    #[allow(clippy::type_complexity)]
+    pub fn get(&self, mode: u8) -> ((U256, U256), (U256, U256)) {
+        self.dict.get(mode.to_string().as_str()).unwrap_or_default()
+    }
+
+    pub fn set(&self, mode: u8, value: ((U256, U256), (U256, U256))) {
+        self.dict.set(mode.to_string().as_str(), value);
+    }
+}
+
+pub struct BonusTokens {
+    dict: Dict,
+}
+
+impl BonusTokens {
+    pub fn instance() -> BonusTokens {
+        BonusTokens {
+            dict: Dict::instance(BONUS_TOKENS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(BONUS_TOKENS)
+    }
+
+    pub fn get(&self, key: &Key) -> U256 {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: U256) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct UnlockDays {
+    dict: Dict,
+}
+
+impl UnlockDays {
+    pub fn instance() -> UnlockDays {
+        UnlockDays {
+            dict: Dict::instance(UNLOCK_DAYS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(UNLOCK_DAYS)
+    }
+
+    pub fn get(&self, key: &Key) -> u64 {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: u64) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct VestingTotals {
+    dict: Dict,
+}
+
+impl VestingTotals {
+    pub fn instance() -> VestingTotals {
+        VestingTotals {
+            dict: Dict::instance(VESTING_TOTALS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(VESTING_TOTALS)
+    }
+
+    pub fn get(&self, key: &Key) -> U256 {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: U256) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct VestingClaimed {
+    dict: Dict,
+}
+
+impl VestingClaimed {
+    pub fn instance() -> VestingClaimed {
+        VestingClaimed {
+            dict: Dict::instance(VESTING_CLAIMED),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(VESTING_CLAIMED)
+    }
+
+    pub fn get(&self, key: &Key) -> U256 {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: U256) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct LpClaimed {
+    dict: Dict,
+}
+
+impl LpClaimed {
+    pub fn instance() -> LpClaimed {
+        LpClaimed {
+            dict: Dict::instance(LP_CLAIMED),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(LP_CLAIMED)
+    }
+
+    pub fn get(&self, key: &Key) -> bool {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: bool) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct DailyTotals {
+    dict: Dict,
+}
+
+impl DailyTotals {
+    pub fn instance() -> DailyTotals {
+        DailyTotals {
+            dict: Dict::instance(DAILY_TOTALS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(DAILY_TOTALS)
+    }
+
+    pub fn get(&self, day: u64) -> U256 {
+        self.dict.get(day.to_string().as_str()).unwrap_or_default()
+    }
+
+    pub fn set(&self, day: u64, value: U256) {
+        self.dict.set(day.to_string().as_str(), value);
+    }
+}
+
+pub struct InvestorDailyBalance {
+    dict: Dict,
+}
+
+impl InvestorDailyBalance {
+    pub fn instance() -> InvestorDailyBalance {
+        InvestorDailyBalance {
+            dict: Dict::instance(INVESTOR_DAILY_BALANCE),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTOR_DAILY_BALANCE)
+    }
+
+    /// Returns (cspr, bonus_cspr, referral_cspr) recorded for an investor on a day
+    pub fn get(&self, key: &Key, day: u64) -> (U256, U256, U256) {
+        self.dict
+            .get(format!("{}_{}", key_to_str(key), day).as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, day: u64, value: (U256, U256, U256)) {
+        self.dict
+            .set(format!("{}_{}", key_to_str(key), day).as_str(), value);
+    }
+}
+
+/// Referral tokens credited to each referrer out of an investor's purchases
+pub struct InvestorReferrals {
+    dict: Dict,
+}
+
+impl InvestorReferrals {
+    pub fn instance() -> InvestorReferrals {
+        InvestorReferrals {
+            dict: Dict::instance(INVESTOR_REFERRALS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTOR_REFERRALS)
+    }
+
+    pub fn get(&self, key: &Key) -> Vec<(Key, U256)> {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: Vec<(Key, U256)>) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+/// An investor's share of InvestmentModeTotals for a mode
+pub struct InvestorModeTotals {
+    dict: Dict,
+}
+
+impl InvestorModeTotals {
+    pub fn instance() -> InvestorModeTotals {
+        InvestorModeTotals {
+            dict: Dict::instance(INVESTOR_MODE_TOTALS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTOR_MODE_TOTALS)
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn get(&self, key: &Key, mode: u8) -> ((U256, U256), (U256, U256)) {
+        self.dict
+            .get(format!("{}_{}", key_to_str(key), mode).as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, mode: u8, value: ((U256, U256), (U256, U256))) {
+        self.dict
+            .set(format!("{}_{}", key_to_str(key), mode).as_str(), value);
+    }
+}
+
+/// Accepted token amounts an investor reserved with, counted in AcceptedTokenTotals
+pub struct InvestorTokenAmounts {
+    dict: Dict,
+}
+
+impl InvestorTokenAmounts {
+    pub fn instance() -> InvestorTokenAmounts {
+        InvestorTokenAmounts {
+            dict: Dict::instance(INVESTOR_TOKEN_AMOUNTS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTOR_TOKEN_AMOUNTS)
+    }
+
+    pub fn get(&self, key: &Key) -> Vec<(Key, U256)> {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: Vec<(Key, U256)>) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct PayoutStakes {
+    dict: Dict,
+}
+
+impl PayoutStakes {
+    pub fn instance() -> PayoutStakes {
+        PayoutStakes {
+            dict: Dict::instance(PAYOUT_STAKES),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(PAYOUT_STAKES)
+    }
+
+    pub fn get(&self, key: &Key) -> Vec<(Vec<u32>, U256)> {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: Vec<(Vec<u32>, U256)>) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct InvestorDayContributions {
+    dict: Dict,
+}
+
+impl InvestorDayContributions {
+    pub fn instance() -> InvestorDayContributions {
+        InvestorDayContributions {
+            dict: Dict::instance(INVESTOR_DAY_CONTRIBUTIONS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTOR_DAY_CONTRIBUTIONS)
+    }
+
+    pub fn get(&self, key: &Key, day: u64) -> U256 {
+        self.dict
+            .get(format!("{}_{}", key_to_str(key), day).as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, day: u64, value: U256) {
+        self.dict
+            .set(format!("{}_{}", key_to_str(key), day).as_str(), value);
+    }
+}
+
+pub struct KycNonces {
+    dict: Dict,
+}
+
+impl KycNonces {
+    pub fn instance() -> KycNonces {
+        KycNonces {
+            dict: Dict::instance(KYC_NONCES),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(KYC_NONCES)
+    }
+
+    pub fn get(&self, key: &Key, nonce: U256) -> bool {
+        self.dict
+            .get(format!("{}_{}", key_to_str(key), nonce).as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, nonce: U256, value: bool) {
+        self.dict
+            .set(format!("{}_{}", key_to_str(key), nonce).as_str(), value);
+    }
+}
+
+pub struct InvestorDays {
+    dict: Dict,
+}
+
+impl InvestorDays {
+    pub fn instance() -> InvestorDays {
+        InvestorDays {
+            dict: Dict::instance(INVESTOR_DAYS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(INVESTOR_DAYS)
+    }
+
+    /// Returns the days of an investor that are not settled yet
+    pub fn get(&self, key: &Key) -> Vec<u64> {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: Vec<u64>) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct CashBackPaid {
+    dict: Dict,
+}
+
+impl CashBackPaid {
+    pub fn instance() -> CashBackPaid {
+        CashBackPaid {
+            dict: Dict::instance(CASH_BACK_PAID),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(CASH_BACK_PAID)
+    }
+
+    pub fn get(&self, key: &Key) -> U256 {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: U256) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+pub struct Refunded {
+    dict: Dict,
+}
+
+impl Refunded {
+    pub fn instance() -> Refunded {
+        Refunded {
+            dict: Dict::instance(REFUNDED),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(REFUNDED)
+    }
+
+    pub fn get(&self, key: &Key) -> bool {
+        self.dict.get(&key_to_str(key)).unwrap_or_default()
+    }
+
+    pub fn set(&self, key: &Key, value: bool) {
+        self.dict.set(&key_to_str(key), value);
+    }
+}
+
+// Investors by slot in `unique_investors` and slot + 1 by address in `investor_index`,
+// slots written before the index existed may hold duplicates until they are migrated
+pub struct InvestorRegistry {
+    investors: Dict,
+    index: Dict,
+}
+
+impl InvestorRegistry {
+    pub fn instance() -> InvestorRegistry {
+        InvestorRegistry {
+            investors: Dict::instance(UNIQUE_INVESTORS),
+            index: Dict::instance(INVESTOR_INDEX),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(UNIQUE_INVESTORS);
+        Self::init_index();
+    }
+
+    // Versions before the registry only stored `unique_investors`
+    pub fn init_index() {
+        Dict::init(INVESTOR_INDEX);
+    }
+
+    pub fn get(&self, slot: &U256) -> Key {
+        self.investors
+            .get(slot.to_string().as_str())
+            .unwrap_or_revert()
+    }
+
+    pub fn slots(&self) -> U256 {
+        Globals::instance().get(INVESTOR_COUNT)
+    }
+
+    pub fn index_of(&self, investor: &Key) -> Option<U256> {
+        let position: U256 = self.index.get(&key_to_str(investor)).unwrap_or_default();
+        if position == U256::from(0) {
+            None
+        } else {
+            Some(position - 1)
+        }
+    }
+
+    pub fn set_index(&self, investor: &Key, slot: U256) {
+        self.index.set(&key_to_str(investor), slot + 1);
+    }
+
+    // Slots left behind by duplicates are skipped when iterating
+    pub fn is_canonical(&self, slot: &U256, investor: &Key) -> bool {
+        self.index_of(investor) == Some(*slot)
+    }
+
+    // Returns true if the investor was not registered yet
+    pub fn register(&self, investor: &Key) -> bool {
+        if self.index_of(investor).is_some() {
+            return false;
+        }
+        let globals = Globals::instance();
+        let slot: U256 = self.slots();
+        self.investors.set(slot.to_string().as_str(), *investor);
+        self.set_index(investor, slot);
+        globals.set(INVESTOR_COUNT, slot + 1);
+        let unique: U256 = globals.get(UNIQUE_INVESTOR_COUNT);
+        globals.set(UNIQUE_INVESTOR_COUNT, unique + 1);
+        true
+    }
+}
+
+// Referrers that never reserved themselves, by slot in `referrers` and slot + 1 by address
+// in `referrer_index`, kept apart from the investor registry so they only extend payouts
+pub struct ReferrerRegistry {
+    referrers: Dict,
+    index: Dict,
+}
+
+impl ReferrerRegistry {
+    pub fn instance() -> ReferrerRegistry {
+        ReferrerRegistry {
+            referrers: Dict::instance(REFERRERS),
+            index: Dict::instance(REFERRER_INDEX),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(REFERRERS);
+        Dict::init(REFERRER_INDEX);
+    }
+
+    pub fn get(&self, slot: &U256) -> Key {
+        self.referrers
+            .get(slot.to_string().as_str())
+            .unwrap_or_revert()
+    }
+
+    pub fn slots(&self) -> U256 {
+        Globals::instance().get(REFERRER_COUNT)
+    }
+
+    // Returns true if the referrer was not registered yet
+    pub fn register(&self, referrer: &Key) -> bool {
+        let position: U256 = self.index.get(&key_to_str(referrer)).unwrap_or_default();
+        if position > U256::from(0) {
+            return false;
+        }
+        let slot: U256 = self.slots();
+        self.referrers.set(slot.to_string().as_str(), *referrer);
+        self.index.set(&key_to_str(referrer), slot + 1);
+        Globals::instance().set(REFERRER_COUNT, slot + 1);
+        true
+    }
+}
+
+pub struct SwapRoutes {
     dict: Dict,
 }
 
-impl UniqueInvestors {
-    pub fn instance() -> UniqueInvestors {
-        UniqueInvestors {
-            dict: Dict::instance(UNIQUE_INVESTORS),
+impl SwapRoutes {
+    pub fn instance() -> SwapRoutes {
+        SwapRoutes {
+            dict: Dict::instance(SWAP_ROUTES),
         }
     }
 
     pub fn init() {
-        Dict::init(UNIQUE_INVESTORS)
+        Dict::init(SWAP_ROUTES)
     }
 
-    pub fn get(&self, owner: &U256) -> Key {
-        self.dict.get(owner.to_string().as_str()).unwrap()
+    pub fn get(&self, token: &Key) -> Vec<Key> {
+        self.dict.get(&key_to_str(token)).unwrap_or_default()
     }
 
-    pub fn set(&self, owner: &U256, value: Key) {
-        self.dict.set(owner.to_string().as_str(), value);
+    pub fn set(&self, token: &Key, value: Vec<Key>) {
+        self.dict.set(&key_to_str(token), value);
+    }
+}
+
+pub struct AcceptedTokens {
+    dict: Dict,
+}
+
+impl AcceptedTokens {
+    pub fn instance() -> AcceptedTokens {
+        AcceptedTokens {
+            dict: Dict::instance(ACCEPTED_TOKENS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(ACCEPTED_TOKENS)
+    }
+
+    /// Returns (accepted, min_amount, max_amount) of a token
+    pub fn get(&self, token: &Key) -> (bool, U256, U256) {
+        self.dict.get(&key_to_str(token)).unwrap_or_default()
+    }
+
+    pub fn set(&self, token: &Key, value: (bool, U256, U256)) {
+        self.dict.set(&key_to_str(token), value);
+    }
+}
+
+pub struct AcceptedTokenTotals {
+    dict: Dict,
+}
+
+impl AcceptedTokenTotals {
+    pub fn instance() -> AcceptedTokenTotals {
+        AcceptedTokenTotals {
+            dict: Dict::instance(ACCEPTED_TOKEN_TOTALS),
+        }
+    }
+
+    pub fn init() {
+        Dict::init(ACCEPTED_TOKEN_TOTALS)
+    }
+
+    pub fn get(&self, token: &Key) -> U256 {
+        self.dict.get(&key_to_str(token)).unwrap_or_default()
+    }
+
+    pub fn set(&self, token: &Key, value: U256) {
+        self.dict.set(&key_to_str(token), value);
     }
 }
 
@@ -211,6 +937,161 @@ pub fn set_settings_keeper(hash: Key) {
     set_key(SETTINGS_KEEPER, hash);
 }
 
+pub fn investment_days() -> u8 {
+    get_key(INVESTMENT_DAYS).unwrap_or_default()
+}
+
+pub fn set_investment_days(investment_days: u8) {
+    set_key(INVESTMENT_DAYS, investment_days);
+}
+
+pub fn max_supply() -> U256 {
+    get_key(MAX_SUPPLY).unwrap_or_default()
+}
+
+pub fn set_max_supply(max_supply: U256) {
+    set_key(MAX_SUPPLY, max_supply);
+}
+
+pub fn max_invest() -> U256 {
+    get_key(MAX_INVEST).unwrap_or_default()
+}
+
+pub fn set_max_invest(max_invest: U256) {
+    set_key(MAX_INVEST, max_invest);
+}
+
+pub fn token_cost() -> U256 {
+    get_key(TOKEN_COST).unwrap_or_default()
+}
+
+pub fn set_token_cost(token_cost: U256) {
+    set_key(TOKEN_COST, token_cost);
+}
+
+pub fn refund_cap() -> U256 {
+    get_key(REFUND_CAP).unwrap_or_default()
+}
+
+pub fn set_refund_cap(refund_cap: U256) {
+    set_key(REFUND_CAP, refund_cap);
+}
+
+pub fn referral_bonus() -> U256 {
+    get_key(REFERRAL_BONUS).unwrap_or_default()
+}
+
+pub fn set_referral_bonus(referral_bonus: U256) {
+    set_key(REFERRAL_BONUS, referral_bonus);
+}
+
+pub fn vesting_cliff_days() -> u64 {
+    get_key(VESTING_CLIFF_DAYS).unwrap_or_default()
+}
+
+pub fn set_vesting_cliff_days(vesting_cliff_days: u64) {
+    set_key(VESTING_CLIFF_DAYS, vesting_cliff_days);
+}
+
+pub fn vesting_days() -> u64 {
+    get_key(VESTING_DAYS).unwrap_or_default()
+}
+
+pub fn set_vesting_days(vesting_days: u64) {
+    set_key(VESTING_DAYS, vesting_days);
+}
+
+pub fn lp_destination() -> u8 {
+    get_key(LP_DESTINATION).unwrap_or_default()
+}
+
+pub fn set_lp_destination(lp_destination: u8) {
+    set_key(LP_DESTINATION, lp_destination);
+}
+
+pub fn lp_recipient() -> Key {
+    get_key(LP_RECIPIENT).unwrap_or_else(zero_address)
+}
+
+pub fn set_lp_recipient(lp_recipient: Key) {
+    set_key(LP_RECIPIENT, lp_recipient);
+}
+
+pub fn price_tolerance() -> U256 {
+    get_key(PRICE_TOLERANCE).unwrap_or_default()
+}
+
+pub fn set_price_tolerance(price_tolerance: U256) {
+    set_key(PRICE_TOLERANCE, price_tolerance);
+}
+
+pub fn sale_mode() -> u8 {
+    get_key(SALE_MODE).unwrap_or_default()
+}
+
+pub fn set_sale_mode(sale_mode: u8) {
+    set_key(SALE_MODE, sale_mode);
+}
+
+/// Returns (curve_type, factor, step) of the bonding curve
+pub fn bonding_curve() -> (u8, U256, U256) {
+    get_key(BONDING_CURVE).unwrap_or_default()
+}
+
+pub fn set_bonding_curve(bonding_curve: (u8, U256, U256)) {
+    set_key(BONDING_CURVE, bonding_curve);
+}
+
+pub fn soft_cap() -> U256 {
+    get_key(SOFT_CAP).unwrap_or_default()
+}
+
+pub fn set_soft_cap(soft_cap: U256) {
+    set_key(SOFT_CAP, soft_cap);
+}
+
+pub fn withdraw_penalty() -> U256 {
+    get_key(WITHDRAW_PENALTY).unwrap_or_default()
+}
+
+pub fn set_withdraw_penalty(withdraw_penalty: U256) {
+    set_key(WITHDRAW_PENALTY, withdraw_penalty);
+}
+
+/// Returns (min_investment, max_investment, daily_cap) per investor, zero disables a limit
+pub fn investor_limits() -> (U256, U256, U256) {
+    get_key(INVESTOR_LIMITS).unwrap_or_default()
+}
+
+pub fn set_investor_limits(investor_limits: (U256, U256, U256)) {
+    set_key(INVESTOR_LIMITS, investor_limits);
+}
+
+pub fn compliance_mode() -> bool {
+    get_key(COMPLIANCE_MODE).unwrap_or_default()
+}
+
+pub fn set_compliance_mode(compliance_mode: bool) {
+    set_key(COMPLIANCE_MODE, compliance_mode);
+}
+
+pub fn kyc_signer() -> Option<PublicKey> {
+    get_key(KYC_SIGNER).unwrap_or_default()
+}
+
+pub fn set_kyc_signer(kyc_signer: Option<PublicKey>) {
+    set_key(KYC_SIGNER, kyc_signer);
+}
+
+/// Hex encoded Merkle root of the (address, cap) allowlist, None when reservations are open
+pub fn allowlist_root() -> Option<String> {
+    get_key(ALLOWLIST_ROOT).unwrap_or_default()
+}
+
+pub fn set_allowlist_root(allowlist_root: Option<String>) {
+    set_key(ALLOWLIST_ROOT, allowlist_root);
+}
+
 pub fn self_purse() -> URef {
     let destination_purse_key = runtime::get_key(SELF_PURSE).unwrap_or_revert();
     match destination_purse_key.as_uref() {
@@ -3,14 +3,18 @@ use alloc::vec::Vec;
 
 use alloc::{
     collections::BTreeMap,
+    format,
     string::{String, ToString},
 };
 use casper_contract::{
     contract_api::{runtime, storage, system},
     unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_types::{runtime_args, ApiError, Key, RuntimeArgs, URef, U256, U512};
+use casper_types::{
+    crypto, runtime_args, ApiError, Key, PublicKey, RuntimeArgs, Signature, URef, U128, U256, U512,
+};
 use casperlabs_contract_utils::{ContractContext, ContractStorage};
+use liquidity_transformer_allowlist::{leaf_hash, node_hash, Hash};
 use num_traits::cast::AsPrimitive;
 
 use crate::data::{self, *};
@@ -27,6 +31,52 @@ pub enum Error {
     InvestmentBelowMinimum,
     OngoingInvestmentPhase,
     ForwardLiquidityFirst,
+    InvalidSaleParameters,
+    SwapDeadlineExpired,
+    SwapBelowMinimumOutput,
+    InvalidSwapPath,
+    TokenNotAccepted,
+    TokenAmountBelowMinimum,
+    TokenCapReached,
+    InvalidReferralBonus,
+    InvalidReferral,
+    InvalidInvestmentModeSettings,
+    SaleAlreadyStarted,
+    TokensLocked,
+    NothingToStake,
+    InvalidLpDestination,
+    LpDistributionDisabled,
+    LpAlreadyClaimed,
+    LpTransferFailed,
+    InvalidPriceTolerance,
+    PoolPriceOutOfRange,
+    InvalidSaleMode,
+    InvalidBondingCurve,
+    SoftCapNotReached,
+    SaleCancelled,
+    InvalidWithdrawPenalty,
+    WithdrawAmountTooHigh,
+    WithdrawNotAvailable,
+    RegistryNotMigrated,
+    RegistryAlreadyMigrated,
+    InvalidInvestorLimits,
+    InvestorMinimumNotReached,
+    InvestorMaximumExceeded,
+    InvestorDailyCapExceeded,
+    InvalidKycSigner,
+    KycApprovalRequired,
+    KycApprovalExpired,
+    KycNonceUsed,
+    KycAmountExceeded,
+    InvalidKycSignature,
+    InvalidAllowlistRoot,
+    AllowlistProofRequired,
+    AllowlistCapExceeded,
+    InvalidAllowlistProof,
+    SaleNotFailed,
+    SaleFailureAlreadyMarked,
+    InvalidLpLocker,
+    DustTransferFailed,
 }
 
 impl From<Error> for ApiError {
@@ -38,15 +88,20 @@ impl From<Error> for ApiError {
 pub enum LiquidityTransformerEvent {
     WiseReservation {
         sender_address: Key,
+        beneficiary_address: Key,
         investment_amount: U256,
         token_amount: U256,
         current_stakeable_day: u64,
         investment_mode: u8,
+        referral_address: Option<Key>,
     },
     UniswapSwapResult {
         amount_token_a: U256,
         amount_token_b: U256,
         liquidity: U256,
+        lp_destination: u8,
+        lp_recipient: Key,
+        unlock_day: u64,
     },
     CashBackIssued {
         investor_address: Key,
@@ -57,6 +112,44 @@ pub enum LiquidityTransformerEvent {
         investor_address: Key,
         refund_amount: U256,
     },
+    PayoutBatch {
+        start: U256,
+        end: U256,
+        investors_paid: U256,
+        tokens_paid: U256,
+    },
+    PayoutStaked {
+        investor_address: Key,
+        payout: U256,
+        stake_id: Vec<u32>,
+        lock_days: u64,
+        referrer: Key,
+    },
+    LpClaimed {
+        investor_address: Key,
+        amount: U256,
+    },
+    SaleFailed {
+        total_cspr_contributed: U256,
+        soft_cap: U256,
+    },
+    SaleCancelled {
+        total_cspr_contributed: U256,
+        cash_back_total: U256,
+    },
+    SaleClosed {
+        close_day: u64,
+        total_cspr_contributed: U256,
+        total_transfer_tokens: U256,
+    },
+    ReservationWithdrawn {
+        investor_address: Key,
+        amount: U256,
+        token_amount: U256,
+        penalty: U256,
+        cash_back_deducted: U256,
+        payout: U256,
+    },
 }
 
 impl LiquidityTransformerEvent {
@@ -64,15 +157,20 @@ impl LiquidityTransformerEvent {
         match self {
             LiquidityTransformerEvent::WiseReservation {
                 sender_address: _,
+                beneficiary_address: _,
                 investment_amount: _,
                 token_amount: _,
                 current_stakeable_day: _,
                 investment_mode: _,
+                referral_address: _,
             } => "wiseReservation",
             LiquidityTransformerEvent::UniswapSwapResult {
                 amount_token_a: _,
                 amount_token_b: _,
                 liquidity: _,
+                lp_destination: _,
+                lp_recipient: _,
+                unlock_day: _,
             } => "uniswapSwapResult",
             LiquidityTransformerEvent::CashBackIssued {
                 investor_address: _,
@@ -83,6 +181,44 @@ impl LiquidityTransformerEvent {
                 investor_address: _,
                 refund_amount: _,
             } => "refundIssued",
+            LiquidityTransformerEvent::PayoutBatch {
+                start: _,
+                end: _,
+                investors_paid: _,
+                tokens_paid: _,
+            } => "payoutBatch",
+            LiquidityTransformerEvent::PayoutStaked {
+                investor_address: _,
+                payout: _,
+                stake_id: _,
+                lock_days: _,
+                referrer: _,
+            } => "payoutStaked",
+            LiquidityTransformerEvent::LpClaimed {
+                investor_address: _,
+                amount: _,
+            } => "lpClaimed",
+            LiquidityTransformerEvent::SaleFailed {
+                total_cspr_contributed: _,
+                soft_cap: _,
+            } => "saleFailed",
+            LiquidityTransformerEvent::SaleCancelled {
+                total_cspr_contributed: _,
+                cash_back_total: _,
+            } => "saleCancelled",
+            LiquidityTransformerEvent::SaleClosed {
+                close_day: _,
+                total_cspr_contributed: _,
+                total_transfer_tokens: _,
+            } => "saleClosed",
+            LiquidityTransformerEvent::ReservationWithdrawn {
+                investor_address: _,
+                amount: _,
+                token_amount: _,
+                penalty: _,
+                cash_back_deducted: _,
+                payout: _,
+            } => "reservationWithdrawn",
         }
         .to_string()
     }
@@ -101,7 +237,22 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         package_hash: Key,
         contract_hash: Key,
         purse: URef,
+        investment_days: u8,
+        max_supply: U256,
+        max_invest: U256,
+        token_cost: U256,
+        refund_cap: U256,
+        vesting_cliff_days: u64,
+        vesting_days: u64,
     ) {
+        self._check_sale_parameters(
+            investment_days,
+            max_supply,
+            max_invest,
+            token_cost,
+            vesting_cliff_days,
+            vesting_days,
+        );
         data::set_wise(wise);
         data::set_scspr(scspr);
         data::set_pair_wise(pair_wise);
@@ -112,17 +263,180 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         data::set_package(package_hash);
         data::set_settings_keeper(self.get_caller());
         data::set_self_purse(purse);
+        data::set_investment_days(investment_days);
+        data::set_max_supply(max_supply);
+        data::set_max_invest(max_invest);
+        data::set_token_cost(token_cost);
+        data::set_refund_cap(refund_cap);
+        data::set_vesting_cliff_days(vesting_cliff_days);
+        data::set_vesting_days(vesting_days);
+        data::set_lp_destination(LP_DESTINATION_BURN);
+        data::set_lp_recipient(data::zero_address());
+        data::set_price_tolerance(DEFAULT_PRICE_TOLERANCE.into());
+        data::set_sale_mode(SALE_MODE_FIXED_PRICE);
+        data::set_bonding_curve((CURVE_LINEAR, 0.into(), 0.into()));
+        data::set_soft_cap(0.into());
+        data::set_withdraw_penalty(0.into());
+        data::set_investor_limits((0.into(), 0.into(), 0.into()));
+        data::set_compliance_mode(false);
+        data::set_kyc_signer(None);
+        data::set_allowlist_root(None);
 
         Globals::init();
-        UniqueInvestors::init();
+        InvestorRegistry::init();
+        // Fresh deployments have no legacy slots to migrate
+        data::Globals::instance().set(REGISTRY_MIGRATED, true);
         PurchasedTokens::init();
         InvestorBalance::init();
+        SwapRoutes::init();
+        AcceptedTokens::init();
+        AcceptedTokenTotals::init();
+        ReferralTokens::init();
+        InvestmentModes::init();
+        InvestmentModeTotals::init();
+        BonusTokens::init();
+        UnlockDays::init();
+        VestingTotals::init();
+        VestingClaimed::init();
+        LpClaimed::init();
+        DailyTotals::init();
+        InvestorDailyBalance::init();
+        InvestorDays::init();
+        CashBackPaid::init();
+        Refunded::init();
+        InvestorDayContributions::init();
+        KycNonces::init();
+        InvestorReferrals::init();
+        InvestorModeTotals::init();
+        InvestorTokenAmounts::init();
+        ReferrerRegistry::init();
+        PayoutStakes::init();
+
+        // Mode 0 keeps the original 1% cash back until the keeper changes the table
+        InvestmentModes::instance().set(0, (1.into(), 0.into(), 0));
+    }
+
+    // Brings the storage of an older version up to date, values that already exist are
+    // kept so upgrading a running sale does not reset it
+    #[allow(clippy::too_many_arguments)]
+    fn upgrade(
+        &self,
+        contract_hash: Key,
+        investment_days: u8,
+        max_supply: U256,
+        max_invest: U256,
+        token_cost: U256,
+        refund_cap: U256,
+        vesting_cliff_days: u64,
+        vesting_days: u64,
+    ) {
+        data::set_hash(contract_hash);
+        if !runtime::has_key(INVESTMENT_DAYS) {
+            self._check_sale_parameters(
+                investment_days,
+                max_supply,
+                max_invest,
+                token_cost,
+                vesting_cliff_days,
+                vesting_days,
+            );
+            data::set_investment_days(investment_days);
+            data::set_max_supply(max_supply);
+            data::set_max_invest(max_invest);
+            data::set_token_cost(token_cost);
+            data::set_refund_cap(refund_cap);
+            data::set_vesting_cliff_days(vesting_cliff_days);
+            data::set_vesting_days(vesting_days);
+        }
+
+        if !runtime::has_key(PRICE_TOLERANCE) {
+            data::set_price_tolerance(DEFAULT_PRICE_TOLERANCE.into());
+        }
+        if !runtime::has_key(INVESTMENT_MODES) {
+            InvestmentModes::init();
+            InvestmentModes::instance().set(0, (1.into(), 0.into(), 0));
+        }
+        let dictionaries: [(&str, fn()); 24] = [
+            (PURCHASED_TOKENS, PurchasedTokens::init),
+            (INVESTOR_BALANCE, InvestorBalance::init),
+            (SWAP_ROUTES, SwapRoutes::init),
+            (ACCEPTED_TOKENS, AcceptedTokens::init),
+            (ACCEPTED_TOKEN_TOTALS, AcceptedTokenTotals::init),
+            (REFERRAL_TOKENS, ReferralTokens::init),
+            (INVESTMENT_MODE_TOTALS, InvestmentModeTotals::init),
+            (BONUS_TOKENS, BonusTokens::init),
+            (UNLOCK_DAYS, UnlockDays::init),
+            (VESTING_TOTALS, VestingTotals::init),
+            (VESTING_CLAIMED, VestingClaimed::init),
+            (LP_CLAIMED, LpClaimed::init),
+            (DAILY_TOTALS, DailyTotals::init),
+            (INVESTOR_DAILY_BALANCE, InvestorDailyBalance::init),
+            (INVESTOR_DAYS, InvestorDays::init),
+            (CASH_BACK_PAID, CashBackPaid::init),
+            (REFUNDED, Refunded::init),
+            (INVESTOR_DAY_CONTRIBUTIONS, InvestorDayContributions::init),
+            (KYC_NONCES, KycNonces::init),
+            (INVESTOR_REFERRALS, InvestorReferrals::init),
+            (INVESTOR_MODE_TOTALS, InvestorModeTotals::init),
+            (INVESTOR_TOKEN_AMOUNTS, InvestorTokenAmounts::init),
+            (REFERRERS, ReferrerRegistry::init),
+            (PAYOUT_STAKES, PayoutStakes::init),
+        ];
+        for (name, init) in dictionaries.iter() {
+            if !runtime::has_key(name) {
+                init();
+            }
+        }
+
+        // Slots written before the registry may hold duplicates until they are migrated
+        if !runtime::has_key(INVESTOR_INDEX) {
+            InvestorRegistry::init_index();
+            data::Globals::instance().set(REGISTRY_CURSOR, U256::from(0));
+            data::Globals::instance().set(REGISTRY_MIGRATED, false);
+        }
+    }
+
+    fn _check_sale_parameters(
+        &self,
+        investment_days: u8,
+        max_supply: U256,
+        max_invest: U256,
+        token_cost: U256,
+        vesting_cliff_days: u64,
+        vesting_days: u64,
+    ) {
+        let unit: U256 = U256::from(1_000_000_000_u128);
+        if investment_days == 0
+            || max_supply < unit
+            || max_invest == U256::from(0)
+            || token_cost == U256::from(0)
+            || vesting_cliff_days > vesting_days
+        {
+            runtime::revert(ApiError::from(Error::InvalidSaleParameters));
+        }
+    }
+
+    // The fixed price refunds the value above MAX_INVEST once MAX_SUPPLY is sold, so its token
+    // cost has to be MAX_INVEST / (MAX_SUPPLY / 1E9) like the original constants. The daily and
+    // bonding curve modes use TOKEN_COST differently and are not bound to it
+    fn _check_fixed_price(&self) {
+        let unit: U256 = U256::from(1_000_000_000_u128);
+        if data::token_cost() != data::max_invest() / (data::max_supply() / unit) {
+            runtime::revert(ApiError::from(Error::InvalidSaleParameters));
+        }
     }
 
     // --- MODIFIERS --- //
 
     fn after_investment_days(&self) {
-        if self.current_stakeable_day() <= data::INVESTMENT_DAYS as u64 {
+        // A sale closed at the hard cap ends the day after it sold out
+        let close_day: u64 = data::Globals::instance().get(CLOSE_DAY);
+        let last_day: u64 = if close_day > 0 {
+            close_day.min(data::investment_days() as u64)
+        } else {
+            data::investment_days() as u64
+        };
+        if self.current_stakeable_day() <= last_day {
             runtime::revert(ApiError::from(Error::OngoingInvestmentPhase));
         }
     }
@@ -134,21 +448,53 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         }
     }
 
+    fn before_uniswap_transfer(&self) {
+        if data::Globals::instance().get(UNISWAP_SWAPED) {
+            runtime::revert(ApiError::from(Error::Swapped));
+        }
+    }
+
     fn below_maximum_invest(&self) {
         let ret: U256 = data::Globals::instance().get(TOTAL_TRANSFER_TOKENS);
-        if ret >= U256::from(data::MAX_SUPPLY) {
+        // Daily sale mode splits a fixed supply per day, so there is no overall cap to hit
+        if data::sale_mode() == SALE_MODE_FIXED_PRICE && ret >= data::max_supply() {
             runtime::revert(ApiError::from(Error::ReserveWiseMaxSupplyReached));
         }
     }
 
+    fn not_cancelled(&self) {
+        if data::Globals::instance().get(SALE_CANCELLED) {
+            runtime::revert(ApiError::from(Error::SaleCancelled));
+        }
+    }
+
     fn below_maximum_day(&self) {
         if self.current_stakeable_day() == 0
-            || self.current_stakeable_day() > data::INVESTMENT_DAYS as u64
+            || self.current_stakeable_day() > data::investment_days() as u64
         {
             runtime::revert(ApiError::from(Error::ReserveWrongInvestmentDay));
         }
     }
 
+    fn accepted_token_amount(&self, token_address: Key, token_amount: U256) {
+        let (accepted, min_amount, max_amount): (bool, U256, U256) =
+            AcceptedTokens::instance().get(&token_address);
+        if !accepted {
+            runtime::revert(ApiError::from(Error::TokenNotAccepted));
+        }
+        if token_amount < min_amount {
+            runtime::revert(ApiError::from(Error::TokenAmountBelowMinimum));
+        }
+        let total: U256 = AcceptedTokenTotals::instance()
+            .get(&token_address)
+            .checked_add(token_amount)
+            .unwrap_or_revert();
+        if max_amount > U256::from(0) && total > max_amount {
+            runtime::revert(ApiError::from(Error::TokenCapReached));
+        }
+        AcceptedTokenTotals::instance().set(&token_address, total);
+    }
+
     fn only_keeper(&self) {
         if self.get_caller() != data::settings_keeper() {
             runtime::revert(ApiError::from(Error::NotKeeper));
@@ -165,34 +511,478 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         data::set_scspr(synthetic_cspr);
     }
 
+    fn set_swap_route(&self, token_address: Key, intermediate_tokens: Vec<Key>) {
+        self.only_keeper();
+        SwapRoutes::instance().set(&token_address, intermediate_tokens);
+    }
+
+    fn set_accepted_token(
+        &self,
+        token_address: Key,
+        accepted: bool,
+        min_amount: U256,
+        max_amount: U256,
+    ) {
+        self.only_keeper();
+        AcceptedTokens::instance().set(&token_address, (accepted, min_amount, max_amount));
+    }
+
+    fn accepted_token(&self, token_address: Key) -> ((bool, U256, U256), U256) {
+        (
+            AcceptedTokens::instance().get(&token_address),
+            AcceptedTokenTotals::instance().get(&token_address),
+        )
+    }
+
+    fn set_referral_bonus(&self, referral_bonus: U256) {
+        self.only_keeper();
+        if referral_bonus > U256::from(100) {
+            runtime::revert(ApiError::from(Error::InvalidReferralBonus));
+        }
+        data::set_referral_bonus(referral_bonus);
+    }
+
+    fn set_investment_mode(
+        &self,
+        investment_mode: u8,
+        cash_back_rate: U256,
+        token_bonus: U256,
+        lock_days: u64,
+    ) {
+        self.only_keeper();
+        if investment_mode >= 6 {
+            runtime::revert(ApiError::from(Error::ReserverWiseWrongMode));
+        }
+        if cash_back_rate > U256::from(100) {
+            runtime::revert(ApiError::from(Error::InvalidInvestmentModeSettings));
+        }
+        let ret: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        if ret > U256::from(0) {
+            runtime::revert(ApiError::from(Error::SaleAlreadyStarted));
+        }
+        InvestmentModes::instance().set(investment_mode, (cash_back_rate, token_bonus, lock_days));
+    }
+
+    fn set_sale_mode(&self, sale_mode: u8) {
+        self.only_keeper();
+        if sale_mode != SALE_MODE_FIXED_PRICE
+            && sale_mode != SALE_MODE_DAILY
+            && sale_mode != SALE_MODE_BONDING_CURVE
+        {
+            runtime::revert(ApiError::from(Error::InvalidSaleMode));
+        }
+        let ret: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        if ret > U256::from(0) {
+            runtime::revert(ApiError::from(Error::SaleAlreadyStarted));
+        }
+        if sale_mode == SALE_MODE_FIXED_PRICE {
+            self._check_fixed_price();
+        }
+        data::set_sale_mode(sale_mode);
+    }
+
+    fn set_bonding_curve(&self, curve_type: u8, factor: U256, step: U256) {
+        self.only_keeper();
+        let valid: bool = match curve_type {
+            CURVE_LINEAR => factor > U256::from(0),
+            CURVE_EXPONENTIAL => {
+                factor > U256::from(0)
+                    && step > U256::from(0)
+                    && data::max_supply() / step <= U256::from(MAX_CURVE_STEPS)
+            }
+            _ => false,
+        };
+        if !valid {
+            runtime::revert(ApiError::from(Error::InvalidBondingCurve));
+        }
+        let ret: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        if ret > U256::from(0) {
+            runtime::revert(ApiError::from(Error::SaleAlreadyStarted));
+        }
+        data::set_bonding_curve((curve_type, factor, step));
+    }
+
+    fn set_soft_cap(&self, soft_cap: U256) {
+        self.only_keeper();
+        if soft_cap > data::max_invest() {
+            runtime::revert(ApiError::from(Error::InvalidSaleParameters));
+        }
+        let ret: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        if ret > U256::from(0) {
+            runtime::revert(ApiError::from(Error::SaleAlreadyStarted));
+        }
+        data::set_soft_cap(soft_cap);
+    }
+
+    // Sale failed when the investment days ended below the soft cap
+    fn sale_failed(&self) -> bool {
+        let total_cspr_contributed: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        self.current_stakeable_day() > data::investment_days() as u64
+            && total_cspr_contributed < data::soft_cap()
+    }
+
+    // Lets anyone record the failure once the investment days ended below the soft cap,
+    // forward_liquidity reverts in that case so it cannot write the flag itself
+    fn mark_sale_failed(&mut self) {
+        if !self.sale_failed() {
+            runtime::revert(ApiError::from(Error::SaleNotFailed));
+        }
+        if data::Globals::instance().get::<bool>(SALE_FAILED) {
+            runtime::revert(ApiError::from(Error::SaleFailureAlreadyMarked));
+        }
+        self._mark_sale_failed();
+    }
+
+    fn _mark_sale_failed(&self) {
+        data::Globals::instance().set(SALE_FAILED, true);
+        self.emit(&LiquidityTransformerEvent::SaleFailed {
+            total_cspr_contributed: data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED),
+            soft_cap: data::soft_cap(),
+        });
+    }
+
+    fn set_investor_limits(&self, min_investment: U256, max_investment: U256, daily_cap: U256) {
+        self.only_keeper();
+        if data::Globals::instance().get(UNISWAP_SWAPED) {
+            runtime::revert(ApiError::from(Error::Swapped));
+        }
+        if max_investment > U256::from(0) && min_investment > max_investment {
+            runtime::revert(ApiError::from(Error::InvalidInvestorLimits));
+        }
+        data::set_investor_limits((min_investment, max_investment, daily_cap));
+    }
+
+    // CSPR an investor can still reserve today, U256::MAX when no limit applies
+    fn remaining_allowance(&self, investor_address: Key) -> U256 {
+        let (_, max_investment, daily_cap): (U256, U256, U256) = data::investor_limits();
+        let mut allowance: U256 = U256::MAX;
+        if max_investment > U256::from(0) {
+            allowance =
+                max_investment.saturating_sub(InvestorBalance::instance().get(&investor_address));
+        }
+        if daily_cap > U256::from(0) {
+            let day_total: U256 = InvestorDayContributions::instance()
+                .get(&investor_address, self.current_stakeable_day());
+            allowance = allowance.min(daily_cap.saturating_sub(day_total));
+        }
+        allowance
+    }
+
+    fn _check_investor_limits(&self, investor_address: Key, value: U256) {
+        let (min_investment, max_investment, daily_cap): (U256, U256, U256) =
+            data::investor_limits();
+        let total: U256 = InvestorBalance::instance()
+            .get(&investor_address)
+            .checked_add(value)
+            .unwrap_or_revert();
+        if total < min_investment {
+            runtime::revert(ApiError::from(Error::InvestorMinimumNotReached));
+        }
+        if max_investment > U256::from(0) && total > max_investment {
+            runtime::revert(ApiError::from(Error::InvestorMaximumExceeded));
+        }
+        let day: u64 = self.current_stakeable_day();
+        let day_total: U256 = InvestorDayContributions::instance()
+            .get(&investor_address, day)
+            .checked_add(value)
+            .unwrap_or_revert();
+        if daily_cap > U256::from(0) && day_total > daily_cap {
+            runtime::revert(ApiError::from(Error::InvestorDailyCapExceeded));
+        }
+        InvestorDayContributions::instance().set(&investor_address, day, day_total);
+    }
+
+    fn set_allowlist_root(&self, allowlist_root: Option<String>) {
+        self.only_keeper();
+        if data::Globals::instance().get(UNISWAP_SWAPED) {
+            runtime::revert(ApiError::from(Error::Swapped));
+        }
+        // Stored lowercase so it compares with the encoded root computed from a proof
+        let allowlist_root: Option<String> = allowlist_root.map(|allowlist_root| {
+            let mut root: [u8; 32] = [0; 32];
+            if hex::decode_to_slice(allowlist_root, &mut root).is_err() {
+                runtime::revert(ApiError::from(Error::InvalidAllowlistRoot));
+            }
+            hex::encode(root)
+        });
+        data::set_allowlist_root(allowlist_root);
+    }
+
+    // cap limits the investor balance including this reservation
+    fn _verify_allowlist_proof(
+        &self,
+        investor_address: Key,
+        value: U256,
+        allowlist_root: String,
+        allowlist_proof: Option<AllowlistProof>,
+    ) {
+        let (cap, proof): AllowlistProof = match allowlist_proof {
+            Some(allowlist_proof) => allowlist_proof,
+            None => runtime::revert(ApiError::from(Error::AllowlistProofRequired)),
+        };
+        let total: U256 = InvestorBalance::instance()
+            .get(&investor_address)
+            .checked_add(value)
+            .unwrap_or_revert();
+        if total > cap {
+            runtime::revert(ApiError::from(Error::AllowlistCapExceeded));
+        }
+        let mut node: Hash = leaf_hash(&investor_address, cap);
+        for sibling in proof.iter() {
+            let mut sibling_bytes: Hash = [0; 32];
+            if hex::decode_to_slice(sibling, &mut sibling_bytes).is_err() {
+                runtime::revert(ApiError::from(Error::InvalidAllowlistProof));
+            }
+            node = node_hash(node, sibling_bytes);
+        }
+        if hex::encode(node) != allowlist_root {
+            runtime::revert(ApiError::from(Error::InvalidAllowlistProof));
+        }
+    }
+
+    fn set_kyc_signer(&self, kyc_signer: PublicKey) {
+        self.only_keeper();
+        if !matches!(kyc_signer, PublicKey::Secp256k1(_)) {
+            runtime::revert(ApiError::from(Error::InvalidKycSigner));
+        }
+        data::set_kyc_signer(Some(kyc_signer));
+    }
+
+    fn set_compliance_mode(&self, compliance_mode: bool) {
+        self.only_keeper();
+        if compliance_mode && data::kyc_signer().is_none() {
+            runtime::revert(ApiError::from(Error::InvalidKycSigner));
+        }
+        data::set_compliance_mode(compliance_mode);
+    }
+
+    // Hex encoded keccak256 of the approval, bound to this package so it can not be replayed.
+    // The signer signs the UTF-8 bytes of the returned hex string, crypto::verify hashes them
+    // again with SHA-256 before checking the secp256k1 signature
+    fn kyc_digest(
+        &self,
+        caller: Key,
+        investor_address: Key,
+        max_amount: U256,
+        expiry: u64,
+        nonce: U256,
+    ) -> String {
+        let message: String = format!(
+            "{}_{}_{}_{}_{}_{}",
+            data::package().to_formatted_string(),
+            caller.to_formatted_string(),
+            investor_address.to_formatted_string(),
+            max_amount,
+            expiry,
+            nonce
+        );
+        hex::encode(renvm_sig::keccak256(message.as_bytes()))
+    }
+
+    fn kyc_nonce_used(&self, investor_address: Key, nonce: U256) -> bool {
+        KycNonces::instance().get(&investor_address, nonce)
+    }
+
+    // max_amount caps the investor balance including this reservation
+    fn _verify_kyc_approval(
+        &self,
+        investor_address: Key,
+        value: U256,
+        kyc_approval: Option<KycApproval>,
+    ) {
+        let ((max_amount, expiry, nonce), signature): KycApproval = match kyc_approval {
+            Some(kyc_approval) => kyc_approval,
+            None => runtime::revert(ApiError::from(Error::KycApprovalRequired)),
+        };
+        let time: u64 = runtime::get_blocktime().into();
+        if time > expiry {
+            runtime::revert(ApiError::from(Error::KycApprovalExpired));
+        }
+        if KycNonces::instance().get(&investor_address, nonce) {
+            runtime::revert(ApiError::from(Error::KycNonceUsed));
+        }
+        let total: U256 = InvestorBalance::instance()
+            .get(&investor_address)
+            .checked_add(value)
+            .unwrap_or_revert();
+        if total > max_amount {
+            runtime::revert(ApiError::from(Error::KycAmountExceeded));
+        }
+
+        let mut signature_bytes: [u8; 64] = [0; 64];
+        if hex::decode_to_slice(signature, &mut signature_bytes).is_err() {
+            runtime::revert(ApiError::from(Error::InvalidKycSignature));
+        }
+        let signature: Signature = match Signature::secp256k1_from_bytes(signature_bytes) {
+            Ok(signature) => signature,
+            Err(_) => runtime::revert(ApiError::from(Error::InvalidKycSignature)),
+        };
+        let kyc_signer: PublicKey = match data::kyc_signer() {
+            Some(kyc_signer) => kyc_signer,
+            None => runtime::revert(ApiError::from(Error::InvalidKycSigner)),
+        };
+        // Signed for the caller and the credited investor, so only that caller can use it
+        let digest: String = self.kyc_digest(
+            self.get_caller(),
+            investor_address,
+            max_amount,
+            expiry,
+            nonce,
+        );
+        if crypto::verify(digest.as_bytes(), &signature, &kyc_signer).is_err() {
+            runtime::revert(ApiError::from(Error::InvalidKycSignature));
+        }
+        KycNonces::instance().set(&investor_address, nonce, true);
+    }
+
+    fn set_withdraw_penalty(&self, withdraw_penalty: U256) {
+        self.only_keeper();
+        if withdraw_penalty > U256::from(100) {
+            runtime::revert(ApiError::from(Error::InvalidWithdrawPenalty));
+        }
+        data::set_withdraw_penalty(withdraw_penalty);
+    }
+
+    fn set_price_tolerance(&self, price_tolerance: U256) {
+        self.only_keeper();
+        if price_tolerance > U256::from(100) {
+            runtime::revert(ApiError::from(Error::InvalidPriceTolerance));
+        }
+        data::set_price_tolerance(price_tolerance);
+    }
+
+    fn set_lp_destination(&self, lp_destination: u8, lp_recipient: Key) {
+        self.only_keeper();
+        if data::Globals::instance().get(UNISWAP_SWAPED) {
+            runtime::revert(ApiError::from(Error::Swapped));
+        }
+        let valid: bool = match lp_destination {
+            LP_DESTINATION_BURN | LP_DESTINATION_INVESTORS => true,
+            LP_DESTINATION_TREASURY | LP_DESTINATION_LOCKER => lp_recipient != data::zero_address(),
+            _ => false,
+        };
+        if !valid {
+            runtime::revert(ApiError::from(Error::InvalidLpDestination));
+        }
+        if lp_destination == LP_DESTINATION_LOCKER {
+            self._check_lp_locker(lp_recipient);
+        }
+        data::set_lp_destination(lp_destination);
+        match lp_destination {
+            LP_DESTINATION_BURN => data::set_lp_recipient(data::zero_address()),
+            LP_DESTINATION_INVESTORS => data::set_lp_recipient(data::package()),
+            _ => data::set_lp_recipient(lp_recipient),
+        }
+    }
+
+    // Reverts unless the locker holds pair_wise and releases it after today
+    fn _check_lp_locker(&self, lp_locker: Key) {
+        let lp_token: Key = runtime::call_versioned_contract(
+            lp_locker.into_hash().unwrap_or_revert().into(),
+            None,
+            "lp_token",
+            runtime_args! {},
+        );
+        let unlock_day: u64 = runtime::call_versioned_contract(
+            lp_locker.into_hash().unwrap_or_revert().into(),
+            None,
+            "unlock_day",
+            runtime_args! {},
+        );
+        if lp_token != data::pair_wise() || unlock_day <= self.current_stakeable_day() {
+            runtime::revert(ApiError::from(Error::InvalidLpLocker));
+        }
+    }
+
+    fn cancel_sale(&mut self) {
+        self.only_keeper();
+        self.not_cancelled();
+        if data::Globals::instance().get(UNISWAP_SWAPED) {
+            runtime::revert(ApiError::from(Error::Swapped));
+        }
+        data::Globals::instance().set(SALE_CANCELLED, true);
+        self.emit(&LiquidityTransformerEvent::SaleCancelled {
+            total_cspr_contributed: data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED),
+            cash_back_total: data::Globals::instance().get(CASH_BACK_TOTAL),
+        });
+    }
+
+    fn close_day(&self) -> u64 {
+        data::Globals::instance().get(CLOSE_DAY)
+    }
+
+    fn sale_cancelled(&self) -> bool {
+        data::Globals::instance().get(SALE_CANCELLED)
+    }
+
+    fn cash_back_paid(&self, investor_address: Key) -> U256 {
+        CashBackPaid::instance().get(&investor_address)
+    }
+
     fn renounce_keeper(&self) {
         self.only_keeper();
         data::set_settings_keeper(data::zero_address());
     }
 
-    fn reserve_wise(&mut self, investment_mode: u8, msg_value: U256, caller_purse: URef) {
+    #[allow(clippy::too_many_arguments)]
+    fn reserve_wise(
+        &mut self,
+        investment_mode: u8,
+        msg_value: U256,
+        referral_address: Option<Key>,
+        beneficiary: Option<Key>,
+        kyc_approval: Option<KycApproval>,
+        allowlist_proof: Option<AllowlistProof>,
+        caller_purse: URef,
+    ) {
+        self.not_cancelled();
+        self.before_uniswap_transfer();
         self.below_maximum_day();
         self.below_maximum_invest();
-        if msg_value < U256::from(data::TOKEN_COST) {
+        if msg_value < data::token_cost() {
             runtime::revert(ApiError::from(Error::ReserveWiseMinInvest));
         }
         // Payable
         let amount: U512 = <casper_types::U256 as AsPrimitive<casper_types::U512>>::as_(msg_value);
         system::transfer_from_purse_to_purse(caller_purse, data::self_purse(), amount, None)
             .unwrap_or_revert();
-        self._reserve_wise(self.get_caller(), msg_value, investment_mode, caller_purse);
+        self._reserve_wise(
+            beneficiary.unwrap_or_else(|| self.get_caller()),
+            msg_value,
+            investment_mode,
+            referral_address,
+            kyc_approval,
+            allowlist_proof,
+            caller_purse,
+        );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn reserve_wise_with_token(
         &mut self,
         token_address: Key,
         token_amount: U256,
         investment_mode: u8,
+        min_cspr_out: U256,
+        deadline: U256,
+        path: Option<Vec<Key>>,
+        referral_address: Option<Key>,
+        beneficiary: Option<Key>,
+        kyc_approval: Option<KycApproval>,
+        allowlist_proof: Option<AllowlistProof>,
         caller_purse: URef,
     ) {
+        self.not_cancelled();
+        self.before_uniswap_transfer();
         self.below_maximum_day();
         self.below_maximum_invest();
 
+        let time: u64 = runtime::get_blocktime().into();
+        if U256::from(time) > deadline {
+            runtime::revert(ApiError::from(Error::SwapDeadlineExpired));
+        }
+        self.accepted_token_amount(token_address, token_amount);
+
         let args: RuntimeArgs = runtime_args! {
             "owner" => self.get_caller(),
             "recipient" => data::package(),
@@ -216,19 +1006,26 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
             args,
         );
 
-        let _path: Vec<Key> = self.prepare_path(token_address);
-        let path: Vec<String> = vec![
-            _path[0].to_formatted_string(),
-            _path[1].to_formatted_string(),
-        ];
+        let _path: Vec<Key> = match path {
+            Some(path) => {
+                if path.len() < 2
+                    || path[0] != token_address
+                    || path[path.len() - 1] != data::wcspr()
+                {
+                    runtime::revert(ApiError::from(Error::InvalidSwapPath));
+                }
+                path
+            }
+            None => self.prepare_path(token_address),
+        };
+        let path: Vec<String> = _path.iter().map(|key| key.to_formatted_string()).collect();
 
-        let time: u64 = runtime::get_blocktime().into();
         let args: RuntimeArgs = runtime_args! {
             "amount_in" => token_amount,
-            "amount_out_min" => U256::from(0),
+            "amount_out_min" => min_cspr_out,
             "path" => path,
             "to" => data::self_purse(),
-            "deadline" => U256::from(time + 7_200_000)
+            "deadline" => deadline
         };
         let amounts: Vec<U256> = runtime::call_versioned_contract(
             data::uniswap_router().into_hash().unwrap_or_revert().into(),
@@ -237,69 +1034,223 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
             args,
         );
 
-        if amounts[1] < U256::from(data::TOKEN_COST) {
+        let cspr_amount: U256 = amounts[amounts.len() - 1];
+        if cspr_amount < min_cspr_out {
+            runtime::revert(ApiError::from(Error::SwapBelowMinimumOutput));
+        }
+        if cspr_amount < data::token_cost() {
             runtime::revert(ApiError::from(Error::InvestmentBelowMinimum));
         }
 
-        self._reserve_wise(self.get_caller(), amounts[1], investment_mode, caller_purse);
+        let investor_address: Key = beneficiary.unwrap_or_else(|| self.get_caller());
+        self._reserve_wise(
+            investor_address,
+            cspr_amount,
+            investment_mode,
+            referral_address,
+            kyc_approval,
+            allowlist_proof,
+            caller_purse,
+        );
+        // Kept so a withdrawal can give the used part of the token cap back
+        let mut token_amounts: Vec<(Key, U256)> =
+            InvestorTokenAmounts::instance().get(&investor_address);
+        match token_amounts
+            .iter_mut()
+            .find(|(token, _)| *token == token_address)
+        {
+            Some((_, amount)) => *amount = amount.checked_add(token_amount).unwrap_or_revert(),
+            None => token_amounts.push((token_address, token_amount)),
+        }
+        InvestorTokenAmounts::instance().set(&investor_address, token_amounts);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn _reserve_wise(
         &mut self,
-        sender_address: Key,
+        investor_address: Key,
         sender_value: U256,
         investment_mode: u8,
+        referral_address: Option<Key>,
+        kyc_approval: Option<KycApproval>,
+        allowlist_proof: Option<AllowlistProof>,
         caller_purse: URef,
     ) {
         if investment_mode >= 6 {
             runtime::revert(ApiError::from(Error::ReserverWiseWrongMode));
         }
+        if referral_address == Some(investor_address) {
+            runtime::revert(ApiError::from(Error::InvalidReferral));
+        }
 
-        if InvestorBalance::instance().get(&sender_address) == U256::from(0) {
-            let ret: U256 = data::Globals::instance().get(INVESTOR_COUNT);
-            UniqueInvestors::instance().set(&ret, sender_address);
-            let ret: U256 = data::Globals::instance().get(INVESTOR_COUNT);
-            data::Globals::instance().set(INVESTOR_COUNT, ret + 1);
+        // Legacy slots must be indexed first, otherwise their investors would be added again
+        if !data::Globals::instance().get::<bool>(REGISTRY_MIGRATED) {
+            runtime::revert(ApiError::from(Error::RegistryNotMigrated));
+        }
+        InvestorRegistry::instance().register(&investor_address);
+        // Referrers outside the investor registry get their own slots so batch payouts reach them
+        if let Some(referral_address) = referral_address {
+            if InvestorRegistry::instance()
+                .index_of(&referral_address)
+                .is_none()
+            {
+                ReferrerRegistry::instance().register(&referral_address);
+            }
+        }
+        if Refunded::instance().get(&investor_address) {
+            Refunded::instance().set(&investor_address, false);
+            let ret: U256 = data::Globals::instance().get(REFUNDED_INVESTORS);
+            data::Globals::instance().set(
+                REFUNDED_INVESTORS,
+                ret.checked_sub(1.into()).unwrap_or_revert(),
+            );
+        }
+        if InvestorBalance::instance().get(&investor_address) == U256::from(0) {
+            let ret: U256 = data::Globals::instance().get(ACTIVE_INVESTORS);
+            data::Globals::instance().set(ACTIVE_INVESTORS, ret + 1);
         }
 
-        let (sender_tokens, return_amount): (U256, U256) = self._get_token_amount(
-            data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED),
-            data::Globals::instance().get(TOTAL_TRANSFER_TOKENS),
-            sender_value,
-        );
+        let (cash_back_rate, token_bonus, lock_days): (U256, U256, u64) =
+            InvestmentModes::instance().get(investment_mode);
+
+        // Daily sale mode allocates tokens once the day is settled
+        let (sender_tokens, return_amount): (U256, U256) = if data::sale_mode() == SALE_MODE_DAILY {
+            self._record_daily_reservation(
+                investor_address,
+                sender_value,
+                token_bonus,
+                referral_address,
+            );
+            (0.into(), 0.into())
+        } else {
+            self._get_token_amount(
+                data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED),
+                data::Globals::instance().get(TOTAL_TRANSFER_TOKENS),
+                sender_value,
+            )
+        };
+        // Value sent back above the hard cap is never part of the contributions
+        let invested_value: U256 = sender_value.checked_sub(return_amount).unwrap_or_revert();
+        self._check_investor_limits(investor_address, invested_value);
+        if data::compliance_mode() {
+            self._verify_kyc_approval(investor_address, invested_value, kyc_approval);
+        }
+        if let Some(allowlist_root) = data::allowlist_root() {
+            self._verify_allowlist_proof(
+                investor_address,
+                invested_value,
+                allowlist_root,
+                allowlist_proof,
+            );
+        }
 
         let ret: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
-        data::Globals::instance().set(TOTAL_CSPR_CONTRIBUTED, ret + sender_value);
+        data::Globals::instance().set(TOTAL_CSPR_CONTRIBUTED, ret + invested_value);
         let ret: U256 = data::Globals::instance().get(TOTAL_TRANSFER_TOKENS);
         data::Globals::instance().set(TOTAL_TRANSFER_TOKENS, ret + sender_tokens);
+        if ret + sender_tokens >= data::max_supply()
+            && data::sale_mode() != SALE_MODE_DAILY
+            && data::Globals::instance().get::<u64>(CLOSE_DAY) == 0
+        {
+            let close_day: u64 = self.current_stakeable_day();
+            data::Globals::instance().set(CLOSE_DAY, close_day);
+            self.emit(&LiquidityTransformerEvent::SaleClosed {
+                close_day,
+                total_cspr_contributed: data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED),
+                total_transfer_tokens: ret + sender_tokens,
+            });
+        }
 
         InvestorBalance::instance().set(
-            &sender_address,
-            InvestorBalance::instance().get(&sender_address) + sender_value,
+            &investor_address,
+            InvestorBalance::instance().get(&investor_address) + invested_value,
         );
         PurchasedTokens::instance().set(
-            &sender_address,
-            PurchasedTokens::instance().get(&sender_address) + sender_tokens,
+            &investor_address,
+            PurchasedTokens::instance().get(&investor_address) + sender_tokens,
         );
 
+        if let Some(referral_address) = referral_address {
+            let referral_tokens: U256 = sender_tokens
+                .checked_mul(data::referral_bonus())
+                .unwrap_or_revert()
+                .checked_div(100.into())
+                .unwrap_or_revert();
+            ReferralTokens::instance().set(
+                &referral_address,
+                ReferralTokens::instance()
+                    .get(&referral_address)
+                    .checked_add(referral_tokens)
+                    .unwrap_or_revert(),
+            );
+            let ret: U256 = data::Globals::instance().get(REFERRAL_TOKENS_TOTAL);
+            data::Globals::instance().set(
+                REFERRAL_TOKENS_TOTAL,
+                ret.checked_add(referral_tokens).unwrap_or_revert(),
+            );
+            // Kept so a withdrawal can take the referral bonus back from the referrer
+            let mut referrals: Vec<(Key, U256)> =
+                InvestorReferrals::instance().get(&investor_address);
+            match referrals
+                .iter_mut()
+                .find(|(referrer, _)| *referrer == referral_address)
+            {
+                Some((_, tokens)) => {
+                    *tokens = tokens.checked_add(referral_tokens).unwrap_or_revert()
+                }
+                None => referrals.push((referral_address, referral_tokens)),
+            }
+            InvestorReferrals::instance().set(&investor_address, referrals);
+        }
+
+        let bonus_tokens: U256 = sender_tokens
+            .checked_mul(token_bonus)
+            .unwrap_or_revert()
+            .checked_div(100.into())
+            .unwrap_or_revert();
+        if bonus_tokens > U256::from(0) {
+            BonusTokens::instance().set(
+                &investor_address,
+                BonusTokens::instance()
+                    .get(&investor_address)
+                    .checked_add(bonus_tokens)
+                    .unwrap_or_revert(),
+            );
+        }
+
+        if lock_days > 0 {
+            let unlock_day: u64 = data::investment_days() as u64 + lock_days;
+            if unlock_day > UnlockDays::instance().get(&investor_address) {
+                UnlockDays::instance().set(&investor_address, unlock_day);
+            }
+        }
+
+        // Cash back goes to whoever is credited with the reservation, a contract
+        // beneficiary has no purse to pay into and gets none
+        let cash_back_payable: bool =
+            investor_address == self.get_caller() || matches!(investor_address, Key::Account(_));
+        let mut cash_back_issued: U256 = 0.into();
         let ret: U256 = data::Globals::instance().get(CASH_BACK_TOTAL);
-        if investment_mode == 0
-            && ret < U256::from(data::REFUND_CAP)
+        if cash_back_rate > U256::from(0)
+            && cash_back_payable
+            && ret < data::refund_cap()
             && return_amount < sender_value
         {
             let mut cash_back_amount: U256 = sender_value
                 .checked_sub(return_amount)
                 .unwrap_or_revert()
+                .checked_mul(cash_back_rate)
+                .unwrap_or_revert()
                 .checked_div(100.into())
                 .unwrap_or_revert();
 
             let mut cash_back: U256 = data::Globals::instance().get(CASH_BACK_TOTAL);
             cash_back = cash_back.checked_add(cash_back_amount).unwrap_or_revert();
 
-            cash_back_amount = if cash_back < U256::from(REFUND_CAP) {
+            cash_back_amount = if cash_back < data::refund_cap() {
                 cash_back_amount
             } else {
-                U256::from(REFUND_CAP)
+                data::refund_cap()
                     .checked_sub(data::Globals::instance().get(CASH_BACK_TOTAL))
                     .unwrap_or_revert()
             };
@@ -307,21 +1258,83 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
             let mut ret: U256 = data::Globals::instance().get(CASH_BACK_TOTAL);
             ret = ret.checked_add(cash_back_amount).unwrap_or_revert();
             data::Globals::instance().set(CASH_BACK_TOTAL, ret);
-
-            let _ = system::transfer_from_purse_to_purse(
-                data::self_purse(),
-                caller_purse,
-                <casper_types::U256 as AsPrimitive<casper_types::U512>>::as_(cash_back_amount),
-                None,
+            cash_back_issued = cash_back_amount;
+            CashBackPaid::instance().set(
+                &investor_address,
+                CashBackPaid::instance()
+                    .get(&investor_address)
+                    .checked_add(cash_back_amount)
+                    .unwrap_or_revert(),
             );
 
+            let cash_back_value: U512 =
+                <casper_types::U256 as AsPrimitive<casper_types::U512>>::as_(cash_back_amount);
+            match investor_address {
+                Key::Account(beneficiary) if investor_address != self.get_caller() => {
+                    let _ = system::transfer_from_purse_to_account(
+                        data::self_purse(),
+                        beneficiary,
+                        cash_back_value,
+                        None,
+                    );
+                }
+                _ => {
+                    let _ = system::transfer_from_purse_to_purse(
+                        data::self_purse(),
+                        caller_purse,
+                        cash_back_value,
+                        None,
+                    );
+                }
+            }
+
             self.emit(&LiquidityTransformerEvent::CashBackIssued {
-                investor_address: sender_address,
+                investor_address: investor_address,
                 sender_value,
                 cash_back_amount,
             });
         }
 
+        let ((mode_cspr, mode_tokens), (mode_cash_back, mode_bonus)): ((U256, U256), (U256, U256)) =
+            InvestmentModeTotals::instance().get(investment_mode);
+        InvestmentModeTotals::instance().set(
+            investment_mode,
+            (
+                (
+                    mode_cspr.checked_add(invested_value).unwrap_or_revert(),
+                    mode_tokens.checked_add(sender_tokens).unwrap_or_revert(),
+                ),
+                (
+                    mode_cash_back
+                        .checked_add(cash_back_issued)
+                        .unwrap_or_revert(),
+                    mode_bonus.checked_add(bonus_tokens).unwrap_or_revert(),
+                ),
+            ),
+        );
+        let ((investor_cspr, investor_tokens), (investor_cash_back, investor_bonus)): (
+            (U256, U256),
+            (U256, U256),
+        ) = InvestorModeTotals::instance().get(&investor_address, investment_mode);
+        InvestorModeTotals::instance().set(
+            &investor_address,
+            investment_mode,
+            (
+                (
+                    investor_cspr.checked_add(invested_value).unwrap_or_revert(),
+                    investor_tokens
+                        .checked_add(sender_tokens)
+                        .unwrap_or_revert(),
+                ),
+                (
+                    investor_cash_back
+                        .checked_add(cash_back_issued)
+                        .unwrap_or_revert(),
+                    investor_bonus.checked_add(bonus_tokens).unwrap_or_revert(),
+                ),
+            ),
+        );
+
         if return_amount > U256::from(0) {
             system::transfer_from_purse_to_purse(
                 data::self_purse(),
@@ -338,12 +1351,395 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         }
 
         self.emit(&LiquidityTransformerEvent::WiseReservation {
-            sender_address,
-            investment_amount: sender_value,
+            sender_address: self.get_caller(),
+            beneficiary_address: investor_address,
+            investment_amount: invested_value,
             token_amount: sender_tokens,
             current_stakeable_day: self.current_stakeable_day(),
             investment_mode,
+            referral_address,
+        });
+    }
+
+    fn withdraw_reservation(&mut self, amount: U256, caller_purse: URef) -> U256 {
+        self.not_cancelled();
+        // Once the liquidity is formed the totals are final, CLOSE_DAY included
+        self.before_uniswap_transfer();
+        self.below_maximum_day();
+        // Daily records are split pro rata per day and cannot be reduced after the fact
+        if data::sale_mode() == SALE_MODE_DAILY {
+            runtime::revert(ApiError::from(Error::WithdrawNotAvailable));
+        }
+        // A sold out sale can be reopened by a withdrawal on its close day only,
+        // afterwards forward_liquidity may run at any time
+        let close_day: u64 = data::Globals::instance().get(CLOSE_DAY);
+        if close_day > 0 && self.current_stakeable_day() > close_day {
+            runtime::revert(ApiError::from(Error::WithdrawNotAvailable));
+        }
+        let investor_address: Key = self.get_caller();
+        let balance: U256 = InvestorBalance::instance().get(&investor_address);
+        if amount == U256::from(0) || amount > balance {
+            runtime::revert(ApiError::from(Error::WithdrawAmountTooHigh));
+        }
+        // Partial withdrawals keep the reservation above the per investor minimum
+        let (min_investment, _, _): (U256, U256, U256) = data::investor_limits();
+        if amount < balance && balance - amount < min_investment {
+            runtime::revert(ApiError::from(Error::InvestorMinimumNotReached));
+        }
+
+        // Tokens, bonus and cash back are reduced in proportion to the withdrawn CSPR
+        let share = |value: U256| -> U256 { self._share(value, amount, balance) };
+        let purchased: U256 = PurchasedTokens::instance().get(&investor_address);
+        let bonus: U256 = BonusTokens::instance().get(&investor_address);
+        let cash_back_paid: U256 = CashBackPaid::instance().get(&investor_address);
+        let token_amount: U256 = share(purchased);
+        let bonus_amount: U256 = share(bonus);
+        let cash_back_deducted: U256 = share(cash_back_paid);
+        let penalty: U256 = amount
+            .checked_mul(data::withdraw_penalty())
+            .unwrap_or_revert()
+            .checked_div(100.into())
+            .unwrap_or_revert();
+        let payout: U256 = amount
+            .saturating_sub(penalty)
+            .saturating_sub(cash_back_deducted);
+
+        InvestorBalance::instance().set(&investor_address, balance - amount);
+        if amount == balance {
+            self._mark_refunded(investor_address);
+        }
+        PurchasedTokens::instance().set(&investor_address, purchased - token_amount);
+        BonusTokens::instance().set(&investor_address, bonus - bonus_amount);
+        CashBackPaid::instance().set(&investor_address, cash_back_paid - cash_back_deducted);
+
+        let globals = data::Globals::instance();
+        let total_cspr_contributed: U256 = globals.get(TOTAL_CSPR_CONTRIBUTED);
+        globals.set(
+            TOTAL_CSPR_CONTRIBUTED,
+            total_cspr_contributed
+                .checked_sub(amount)
+                .unwrap_or_revert(),
+        );
+        let total_transfer_tokens: U256 = globals.get(TOTAL_TRANSFER_TOKENS);
+        let total_transfer_tokens: U256 = total_transfer_tokens
+            .checked_sub(token_amount)
+            .unwrap_or_revert();
+        globals.set(TOTAL_TRANSFER_TOKENS, total_transfer_tokens);
+        // Supply freed below the hard cap reopens a sale closed early
+        if total_transfer_tokens < data::max_supply() {
+            globals.set(CLOSE_DAY, 0_u64);
+        }
+        let withdraw_penalty_total: U256 = globals.get(WITHDRAW_PENALTY_TOTAL);
+        globals.set(
+            WITHDRAW_PENALTY_TOTAL,
+            withdraw_penalty_total
+                .checked_add(penalty)
+                .unwrap_or_revert(),
+        );
+        self._withdraw_investor_records(investor_address, amount, balance);
+
+        if payout > U256::from(0) {
+            system::transfer_from_purse_to_purse(
+                data::self_purse(),
+                caller_purse,
+                <casper_types::U256 as AsPrimitive<casper_types::U512>>::as_(payout),
+                None,
+            )
+            .unwrap_or_revert();
+        }
+        self.emit(&LiquidityTransformerEvent::ReservationWithdrawn {
+            investor_address,
+            amount,
+            token_amount,
+            penalty,
+            cash_back_deducted,
+            payout,
         });
+        payout
+    }
+
+    fn _share(&self, value: U256, amount: U256, balance: U256) -> U256 {
+        value
+            .checked_mul(amount)
+            .unwrap_or_revert()
+            .checked_div(balance)
+            .unwrap_or_revert()
+    }
+
+    // Takes the withdrawn share out of the referral credits, the investment mode totals,
+    // the accepted token caps and the per day contributions of the investor
+    fn _withdraw_investor_records(&self, investor_address: Key, amount: U256, balance: U256) {
+        let globals = data::Globals::instance();
+
+        let mut referrals: Vec<(Key, U256)> = InvestorReferrals::instance().get(&investor_address);
+        for (referrer, tokens) in referrals.iter_mut() {
+            let referral_tokens: U256 = self._share(*tokens, amount, balance);
+            ReferralTokens::instance().set(
+                referrer,
+                ReferralTokens::instance()
+                    .get(referrer)
+                    .saturating_sub(referral_tokens),
+            );
+            let ret: U256 = globals.get(REFERRAL_TOKENS_TOTAL);
+            globals.set(REFERRAL_TOKENS_TOTAL, ret.saturating_sub(referral_tokens));
+            *tokens = *tokens - referral_tokens;
+        }
+        InvestorReferrals::instance().set(&investor_address, referrals);
+
+        for investment_mode in 0..6_u8 {
+            let ((cspr, tokens), (cash_back, bonus)): ((U256, U256), (U256, U256)) =
+                InvestorModeTotals::instance().get(&investor_address, investment_mode);
+            if cspr == U256::from(0) {
+                continue;
+            }
+            let withdrawn: ((U256, U256), (U256, U256)) = (
+                (
+                    self._share(cspr, amount, balance),
+                    self._share(tokens, amount, balance),
+                ),
+                (
+                    self._share(cash_back, amount, balance),
+                    self._share(bonus, amount, balance),
+                ),
+            );
+            InvestorModeTotals::instance().set(
+                &investor_address,
+                investment_mode,
+                (
+                    (cspr - (withdrawn.0).0, tokens - (withdrawn.0).1),
+                    (cash_back - (withdrawn.1).0, bonus - (withdrawn.1).1),
+                ),
+            );
+            let ((mode_cspr, mode_tokens), (mode_cash_back, mode_bonus)): (
+                (U256, U256),
+                (U256, U256),
+            ) = InvestmentModeTotals::instance().get(investment_mode);
+            InvestmentModeTotals::instance().set(
+                investment_mode,
+                (
+                    (
+                        mode_cspr.saturating_sub((withdrawn.0).0),
+                        mode_tokens.saturating_sub((withdrawn.0).1),
+                    ),
+                    (
+                        mode_cash_back.saturating_sub((withdrawn.1).0),
+                        mode_bonus.saturating_sub((withdrawn.1).1),
+                    ),
+                ),
+            );
+        }
+
+        let mut token_amounts: Vec<(Key, U256)> =
+            InvestorTokenAmounts::instance().get(&investor_address);
+        for (token_address, token_amount) in token_amounts.iter_mut() {
+            let withdrawn: U256 = self._share(*token_amount, amount, balance);
+            AcceptedTokenTotals::instance().set(
+                token_address,
+                AcceptedTokenTotals::instance()
+                    .get(token_address)
+                    .saturating_sub(withdrawn),
+            );
+            *token_amount = *token_amount - withdrawn;
+        }
+        InvestorTokenAmounts::instance().set(&investor_address, token_amounts);
+
+        for day in 1..=self.current_stakeable_day() {
+            let contributed: U256 =
+                InvestorDayContributions::instance().get(&investor_address, day);
+            if contributed > U256::from(0) {
+                InvestorDayContributions::instance().set(
+                    &investor_address,
+                    day,
+                    contributed - self._share(contributed, amount, balance),
+                );
+            }
+        }
+    }
+
+    // Moves the withdraw penalties collected so far out of the contract purse
+    fn sweep_dust(&mut self, recipient: Key) -> (U256, U256) {
+        self.only_keeper();
+        self.after_uniswap_transfer();
+        let globals = data::Globals::instance();
+        let wise_amount: U256 = globals.get(DUST_WISE);
+        let scspr_amount: U256 = globals.get(DUST_SCSPR);
+        globals.set(DUST_WISE, U256::from(0));
+        globals.set(DUST_SCSPR, U256::from(0));
+        for (token, amount) in [(data::wise(), wise_amount), (data::scspr(), scspr_amount)] {
+            if amount == U256::from(0) {
+                continue;
+            }
+            let ret: Result<(), u32> = runtime::call_versioned_contract(
+                token.into_hash().unwrap_or_revert().into(),
+                None,
+                "transfer",
+                runtime_args! {
+                    "recipient" => recipient,
+                    "amount" => amount
+                },
+            );
+            if ret.is_err() {
+                runtime::revert(ApiError::from(Error::DustTransferFailed));
+            }
+        }
+        (wise_amount, scspr_amount)
+    }
+
+    fn dust(&self) -> (U256, U256) {
+        let globals = data::Globals::instance();
+        (globals.get(DUST_WISE), globals.get(DUST_SCSPR))
+    }
+
+    fn sweep_withdraw_penalties(&mut self, treasury_purse: URef) -> U256 {
+        self.only_keeper();
+        let globals = data::Globals::instance();
+        let total: U256 = globals.get(WITHDRAW_PENALTY_TOTAL);
+        let swept: U256 = globals.get(WITHDRAW_PENALTY_SWEPT);
+        let amount: U256 = total.checked_sub(swept).unwrap_or_revert();
+        globals.set(WITHDRAW_PENALTY_SWEPT, total);
+        if amount > U256::from(0) {
+            system::transfer_from_purse_to_purse(
+                data::self_purse(),
+                treasury_purse,
+                <casper_types::U256 as AsPrimitive<casper_types::U512>>::as_(amount),
+                None,
+            )
+            .unwrap_or_revert();
+        }
+        amount
+    }
+
+    fn daily_supply(&self) -> U256 {
+        data::max_supply()
+            .checked_div(data::investment_days().into())
+            .unwrap_or_revert()
+    }
+
+    fn _record_daily_reservation(
+        &self,
+        sender_address: Key,
+        sender_value: U256,
+        token_bonus: U256,
+        referral_address: Option<Key>,
+    ) {
+        let day: u64 = self.current_stakeable_day();
+        let day_total: U256 = DailyTotals::instance().get(day);
+        if day_total == U256::from(0) {
+            let ret: U256 = data::Globals::instance().get(TOTAL_TRANSFER_TOKENS);
+            data::Globals::instance().set(
+                TOTAL_TRANSFER_TOKENS,
+                ret.checked_add(self.daily_supply()).unwrap_or_revert(),
+            );
+        }
+        DailyTotals::instance().set(day, day_total.checked_add(sender_value).unwrap_or_revert());
+
+        let bonus_value: U256 = sender_value
+            .checked_mul(token_bonus)
+            .unwrap_or_revert()
+            .checked_div(100.into())
+            .unwrap_or_revert();
+        self._add_daily_record(sender_address, day, (sender_value, bonus_value, 0.into()));
+        if let Some(referral_address) = referral_address {
+            let referral_value: U256 = sender_value
+                .checked_mul(data::referral_bonus())
+                .unwrap_or_revert()
+                .checked_div(100.into())
+                .unwrap_or_revert();
+            self._add_daily_record(referral_address, day, (0.into(), 0.into(), referral_value));
+        }
+    }
+
+    fn _add_daily_record(&self, investor_address: Key, day: u64, value: (U256, U256, U256)) {
+        let (cspr, bonus, referral): (U256, U256, U256) =
+            InvestorDailyBalance::instance().get(&investor_address, day);
+        if cspr == U256::from(0) && bonus == U256::from(0) && referral == U256::from(0) {
+            let mut days: Vec<u64> = InvestorDays::instance().get(&investor_address);
+            days.push(day);
+            InvestorDays::instance().set(&investor_address, days);
+        }
+        InvestorDailyBalance::instance().set(
+            &investor_address,
+            day,
+            (
+                cspr.checked_add(value.0).unwrap_or_revert(),
+                bonus.checked_add(value.1).unwrap_or_revert(),
+                referral.checked_add(value.2).unwrap_or_revert(),
+            ),
+        );
+    }
+
+    // Splits the supply of every closed day among its contributors
+    // and credits the investor's share to the purchased, bonus and referral tokens
+    fn settle_investor(&self, investor_address: Key) -> U256 {
+        // A cancelled sale takes no more reservations, so the open days are final too
+        let sale_cancelled: bool = data::Globals::instance().get(SALE_CANCELLED);
+        let current_stakeable_day: u64 = self.current_stakeable_day();
+        let daily_supply: U256 = self.daily_supply();
+        let mut open_days: Vec<u64> = Vec::new();
+        let (mut purchased, mut bonus, mut referral): (U256, U256, U256) =
+            (0.into(), 0.into(), 0.into());
+        for day in InvestorDays::instance().get(&investor_address) {
+            if day >= current_stakeable_day && !sale_cancelled {
+                open_days.push(day);
+                continue;
+            }
+            let day_total: U256 = DailyTotals::instance().get(day);
+            let (cspr, bonus_cspr, referral_cspr): (U256, U256, U256) =
+                InvestorDailyBalance::instance().get(&investor_address, day);
+            let share = |value: U256| -> U256 {
+                daily_supply
+                    .checked_mul(value)
+                    .unwrap_or_revert()
+                    .checked_div(day_total)
+                    .unwrap_or_revert()
+            };
+            purchased = purchased.checked_add(share(cspr)).unwrap_or_revert();
+            bonus = bonus.checked_add(share(bonus_cspr)).unwrap_or_revert();
+            referral = referral
+                .checked_add(share(referral_cspr))
+                .unwrap_or_revert();
+        }
+        InvestorDays::instance().set(&investor_address, open_days);
+
+        PurchasedTokens::instance().set(
+            &investor_address,
+            PurchasedTokens::instance()
+                .get(&investor_address)
+                .checked_add(purchased)
+                .unwrap_or_revert(),
+        );
+        BonusTokens::instance().set(
+            &investor_address,
+            BonusTokens::instance()
+                .get(&investor_address)
+                .checked_add(bonus)
+                .unwrap_or_revert(),
+        );
+        ReferralTokens::instance().set(
+            &investor_address,
+            ReferralTokens::instance()
+                .get(&investor_address)
+                .checked_add(referral)
+                .unwrap_or_revert(),
+        );
+        let ret: U256 = data::Globals::instance().get(REFERRAL_TOKENS_TOTAL);
+        data::Globals::instance().set(
+            REFERRAL_TOKENS_TOTAL,
+            ret.checked_add(referral).unwrap_or_revert(),
+        );
+        purchased
+            .checked_add(bonus)
+            .unwrap_or_revert()
+            .checked_add(referral)
+            .unwrap_or_revert()
+    }
+
+    fn daily_totals(&self, day: u64) -> U256 {
+        DailyTotals::instance().get(day)
+    }
+
+    fn investor_daily_balance(&self, investor_address: Key, day: u64) -> (U256, U256, U256) {
+        InvestorDailyBalance::instance().get(&investor_address, day)
     }
 
     fn _get_token_amount(
@@ -352,38 +1748,217 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         total_transfer_tokens: U256,
         sender_value: U256,
     ) -> (U256, U256) {
-        let mut token_amount: U256 = sender_value
-            .checked_div(data::TOKEN_COST.into())
-            .unwrap_or_revert()
-            .checked_mul(U256::from(1_000_000_000_u128))
-            .unwrap_or_revert();
+        let bonding_curve: bool = data::sale_mode() == SALE_MODE_BONDING_CURVE;
+        let mut token_amount: U256 = if bonding_curve {
+            self._curve_tokens(total_transfer_tokens, sender_value)
+        } else {
+            self._check_fixed_price();
+            sender_value
+                .checked_div(data::token_cost())
+                .unwrap_or_revert()
+                .checked_mul(U256::from(1_000_000_000_u128))
+                .unwrap_or_revert()
+        };
 
         let new_supply: U256 = total_transfer_tokens
             .checked_add(token_amount)
             .unwrap_or_revert();
 
         let mut return_amount: U256 = 0.into();
-        if new_supply > U256::from(data::MAX_SUPPLY) {
-            token_amount = U256::from(data::MAX_SUPPLY)
+        if new_supply > data::max_supply() {
+            token_amount = data::max_supply()
                 .checked_sub(total_transfer_tokens)
                 .unwrap_or_revert();
-            let available_value = U256::from(MAX_INVEST)
-                .checked_sub(total_cspr_contributed)
-                .unwrap_or_revert();
+            let available_value = if bonding_curve {
+                self._curve_cost(total_transfer_tokens, data::max_supply())
+            } else {
+                data::max_invest()
+                    .checked_sub(total_cspr_contributed)
+                    .unwrap_or_revert()
+            };
             return_amount = sender_value.checked_sub(available_value).unwrap_or_revert();
         }
 
         (token_amount, return_amount)
     }
 
+    // Tokens bought for value when supply tokens are already sold on the bonding curve,
+    // the price starts at TOKEN_COST and is quoted per 1E9 token units like the flat price
+    fn _curve_tokens(&self, supply: U256, value: U256) -> U256 {
+        let (curve_type, factor, step): (u8, U256, U256) = data::bonding_curve();
+        let unit: U256 = U256::from(1_000_000_000_u128);
+        if curve_type == CURVE_LINEAR {
+            if factor == U256::from(0) {
+                return value
+                    .checked_mul(unit)
+                    .unwrap_or_revert()
+                    .checked_div(data::token_cost())
+                    .unwrap_or_revert();
+            }
+            // price(t) = token_cost + factor * t / 1E9, solved for the end supply of the purchase
+            let start: U256 = factor
+                .checked_mul(supply)
+                .unwrap_or_revert()
+                .checked_add(unit.checked_mul(data::token_cost()).unwrap_or_revert())
+                .unwrap_or_revert();
+            let end: U256 = start
+                .checked_mul(start)
+                .unwrap_or_revert()
+                .checked_add(
+                    unit.checked_mul(unit)
+                        .unwrap_or_revert()
+                        .checked_mul(2.into())
+                        .unwrap_or_revert()
+                        .checked_mul(factor)
+                        .unwrap_or_revert()
+                        .checked_mul(value)
+                        .unwrap_or_revert(),
+                )
+                .unwrap_or_revert()
+                .integer_sqrt();
+            return end
+                .checked_sub(start)
+                .unwrap_or_revert()
+                .checked_div(factor)
+                .unwrap_or_revert();
+        }
+
+        // Exponential curve: the price grows by factor percent every step tokens
+        let (mut price, mut segment_end) = self._curve_segment(supply, factor, step);
+        let (mut supply, mut value, mut tokens): (U256, U256, U256) = (supply, value, 0.into());
+        while value > U256::from(0) {
+            if supply >= data::max_supply() {
+                // Beyond the curve the last price applies, _get_token_amount refunds the excess
+                tokens = tokens
+                    .checked_add(value.checked_mul(unit).unwrap_or_revert() / price)
+                    .unwrap_or_revert();
+                break;
+            }
+            let room: U256 = segment_end.checked_sub(supply).unwrap_or_revert();
+            let room_cost: U256 = room.checked_mul(price).unwrap_or_revert() / unit;
+            if value < room_cost {
+                tokens = tokens
+                    .checked_add(value.checked_mul(unit).unwrap_or_revert() / price)
+                    .unwrap_or_revert();
+                break;
+            }
+            tokens = tokens.checked_add(room).unwrap_or_revert();
+            value = value.checked_sub(room_cost).unwrap_or_revert();
+            supply = segment_end;
+            segment_end = segment_end.checked_add(step).unwrap_or_revert();
+            price = price
+                .checked_mul(U256::from(100).checked_add(factor).unwrap_or_revert())
+                .unwrap_or_revert()
+                / 100;
+        }
+        tokens
+    }
+
+    // Value needed to move the bonding curve supply from `from` to `to`
+    fn _curve_cost(&self, from: U256, to: U256) -> U256 {
+        let (curve_type, factor, step): (u8, U256, U256) = data::bonding_curve();
+        let unit: U256 = U256::from(1_000_000_000_u128);
+        let amount: U256 = to.checked_sub(from).unwrap_or_revert();
+        if curve_type == CURVE_LINEAR {
+            // amount * (2E9 * token_cost + factor * (from + to)) / 2E18
+            return amount
+                .checked_mul(
+                    unit.checked_mul(2.into())
+                        .unwrap_or_revert()
+                        .checked_mul(data::token_cost())
+                        .unwrap_or_revert()
+                        .checked_add(
+                            factor
+                                .checked_mul(from.checked_add(to).unwrap_or_revert())
+                                .unwrap_or_revert(),
+                        )
+                        .unwrap_or_revert(),
+                )
+                .unwrap_or_revert()
+                .checked_div(unit.checked_mul(unit).unwrap_or_revert() * 2)
+                .unwrap_or_revert();
+        }
+
+        let (mut price, mut segment_end) = self._curve_segment(from, factor, step);
+        let (mut supply, mut cost): (U256, U256) = (from, 0.into());
+        while supply < to {
+            let end: U256 = segment_end.min(to);
+            cost = cost
+                .checked_add(
+                    end.checked_sub(supply)
+                        .unwrap_or_revert()
+                        .checked_mul(price)
+                        .unwrap_or_revert()
+                        / unit,
+                )
+                .unwrap_or_revert();
+            supply = end;
+            segment_end = segment_end.checked_add(step).unwrap_or_revert();
+            price = price
+                .checked_mul(U256::from(100).checked_add(factor).unwrap_or_revert())
+                .unwrap_or_revert()
+                / 100;
+        }
+        cost
+    }
+
+    // Price and end of the exponential curve segment containing supply
+    fn _curve_segment(&self, supply: U256, factor: U256, step: U256) -> (U256, U256) {
+        let mut price: U256 = data::token_cost();
+        let mut segment_end: U256 = step;
+        while segment_end <= supply {
+            segment_end = segment_end.checked_add(step).unwrap_or_revert();
+            price = price
+                .checked_mul(U256::from(100).checked_add(factor).unwrap_or_revert())
+                .unwrap_or_revert()
+                / 100;
+        }
+        (price, segment_end)
+    }
+
+    // Allocation and refund a reservation of amount would get right now,
+    // in daily sale mode the share of the current day if no one else joins
+    fn quote_tokens(&self, amount: U256) -> (U256, U256) {
+        if data::sale_mode() == SALE_MODE_DAILY {
+            let day_total: U256 = DailyTotals::instance()
+                .get(self.current_stakeable_day())
+                .checked_add(amount)
+                .unwrap_or_revert();
+            if day_total == U256::from(0) {
+                return (0.into(), 0.into());
+            }
+            return (
+                self.daily_supply()
+                    .checked_mul(amount)
+                    .unwrap_or_revert()
+                    .checked_div(day_total)
+                    .unwrap_or_revert(),
+                0.into(),
+            );
+        }
+        self._get_token_amount(
+            data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED),
+            data::Globals::instance().get(TOTAL_TRANSFER_TOKENS),
+            amount,
+        )
+    }
+
     fn forward_liquidity(&mut self) {
         self.after_investment_days();
         if data::Globals::instance().get(UNISWAP_SWAPED) {
             runtime::revert(ApiError::from(Error::Swapped));
         }
+        self.not_cancelled();
         let scspr_tokens_amount: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
         let wise_tokens_amount: U256 = data::Globals::instance().get(TOTAL_TRANSFER_TOKENS);
         let total_cspr_contributed: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        if total_cspr_contributed < data::soft_cap() {
+            runtime::revert(ApiError::from(Error::SoftCapNotReached));
+        }
+        let dust_reserves: bool = self._dust_reserves(wise_tokens_amount, scspr_tokens_amount);
+        if !dust_reserves {
+            self.check_pool_price(wise_tokens_amount, scspr_tokens_amount);
+        }
 
         let () = runtime::call_versioned_contract(
             data::scspr().into_hash().unwrap_or_revert().into(),
@@ -432,6 +2007,28 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
             },
         );
 
+        // Dust seeded into pair_wise is moved to our price instead of blocking the launch
+        let (wise_budget, scspr_budget): (U256, U256) = (wise_tokens_amount, scspr_tokens_amount);
+        let ((wise_tokens_amount, scspr_tokens_amount), (wise_donated, scspr_donated)) =
+            if dust_reserves {
+                self._align_dust_reserves(wise_tokens_amount, scspr_tokens_amount)
+            } else {
+                (
+                    (wise_tokens_amount, scspr_tokens_amount),
+                    (0.into(), 0.into()),
+                )
+            };
+
+        let price_tolerance: U256 = data::price_tolerance();
+        let amount_a_min: U256 = wise_tokens_amount
+            .checked_mul(U256::from(100) - price_tolerance)
+            .unwrap_or_revert()
+            / 100;
+        let amount_b_min: U256 = scspr_tokens_amount
+            .checked_mul(U256::from(100) - price_tolerance)
+            .unwrap_or_revert()
+            / 100;
+
         let time: u64 = runtime::get_blocktime().into();
         let (amount_token_a, amount_token_b, liquidity): (U256, U256, U256) =
             runtime::call_versioned_contract(
@@ -443,32 +2040,243 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
                     "token_b" => data::scspr(),
                     "amount_a_desired" => wise_tokens_amount,
                     "amount_b_desired" => scspr_tokens_amount,
-                    "amount_a_min" => U256::from(0),
-                    "amount_b_min" => U256::from(0),
-                    "to" => data::zero_address(),
+                    "amount_a_min" => amount_a_min,
+                    "amount_b_min" => amount_b_min,
+                    "to" => data::lp_recipient(),
                     "deadline" => U256::from(time + 7_200_000),
                     "pair" => Some(data::pair_wise())
                 },
             );
 
         data::Globals::instance().set(UNISWAP_SWAPED, true);
+        data::Globals::instance().set(LIQUIDITY_DAY, self.current_stakeable_day());
+        // Whatever the router did not take stays with the transformer until the keeper sweeps it
+        data::Globals::instance().set(
+            DUST_WISE,
+            wise_budget
+                .checked_sub(wise_donated)
+                .and_then(|left| left.checked_sub(amount_token_a))
+                .unwrap_or_revert(),
+        );
+        data::Globals::instance().set(
+            DUST_SCSPR,
+            scspr_budget
+                .checked_sub(scspr_donated)
+                .and_then(|left| left.checked_sub(amount_token_b))
+                .unwrap_or_revert(),
+        );
+        if data::lp_destination() == LP_DESTINATION_INVESTORS {
+            data::Globals::instance().set(LP_TOTAL, liquidity);
+        }
 
+        let unlock_day: u64 = if data::lp_destination() == LP_DESTINATION_LOCKER {
+            runtime::call_versioned_contract(
+                data::lp_recipient().into_hash().unwrap_or_revert().into(),
+                None,
+                "unlock_day",
+                runtime_args! {},
+            )
+        } else {
+            0
+        };
         self.emit(&LiquidityTransformerEvent::UniswapSwapResult {
             amount_token_a,
             amount_token_b,
             liquidity,
+            lp_destination: data::lp_destination(),
+            lp_recipient: data::lp_recipient(),
+            unlock_day,
         });
     }
 
+    fn pool_reserves(&self) -> (U256, U256) {
+        let (reserve0, reserve1, _): (U128, U128, u64) = runtime::call_versioned_contract(
+            data::pair_wise().into_hash().unwrap_or_revert().into(),
+            None,
+            "get_reserves",
+            runtime_args! {},
+        );
+        let token0: Key = runtime::call_versioned_contract(
+            data::pair_wise().into_hash().unwrap_or_revert().into(),
+            None,
+            "token0",
+            runtime_args! {},
+        );
+        let (reserve0, reserve1) = (
+            U256::from(reserve0.as_u128()),
+            U256::from(reserve1.as_u128()),
+        );
+        if token0 == data::wise() {
+            (reserve0, reserve1)
+        } else {
+            (reserve1, reserve0)
+        }
+    }
+
+    fn _dust_reserves(&self, wise_tokens_amount: U256, scspr_tokens_amount: U256) -> bool {
+        let (reserve_wise, reserve_scspr) = self.pool_reserves();
+        (reserve_wise > U256::from(0) || reserve_scspr > U256::from(0))
+            && reserve_wise
+                .checked_mul(DUST_RESERVE_RATIO.into())
+                .unwrap_or_revert()
+                <= wise_tokens_amount
+            && reserve_scspr
+                .checked_mul(DUST_RESERVE_RATIO.into())
+                .unwrap_or_revert()
+                <= scspr_tokens_amount
+    }
+
+    // Tops up the short side of a dust pool to our ratio and syncs the reserves, the amounts
+    // left to add keep the same ratio so the router takes them in full.
+    // Returns the amounts to add and the amounts donated to the pair
+    fn _align_dust_reserves(
+        &self,
+        wise_tokens_amount: U256,
+        scspr_tokens_amount: U256,
+    ) -> ((U256, U256), (U256, U256)) {
+        let (reserve_wise, reserve_scspr) = self.pool_reserves();
+        let pool: U256 = reserve_wise
+            .checked_mul(scspr_tokens_amount)
+            .unwrap_or_revert();
+        let intended: U256 = wise_tokens_amount
+            .checked_mul(reserve_scspr)
+            .unwrap_or_revert();
+        let amounts: ((U256, U256), (U256, U256)) = if pool < intended {
+            let donation: U256 = (intended - pool)
+                .checked_div(scspr_tokens_amount)
+                .unwrap_or_revert();
+            self._transfer_to_pair(data::wise(), donation);
+            let wise_left: U256 = wise_tokens_amount.checked_sub(donation).unwrap_or_revert();
+            (
+                (
+                    wise_left,
+                    scspr_tokens_amount
+                        .checked_mul(wise_left)
+                        .unwrap_or_revert()
+                        .checked_div(wise_tokens_amount)
+                        .unwrap_or_revert(),
+                ),
+                (donation, 0.into()),
+            )
+        } else {
+            let donation: U256 = (pool - intended)
+                .checked_div(wise_tokens_amount)
+                .unwrap_or_revert();
+            self._transfer_to_pair(data::scspr(), donation);
+            let scspr_left: U256 = scspr_tokens_amount.checked_sub(donation).unwrap_or_revert();
+            (
+                (
+                    wise_tokens_amount
+                        .checked_mul(scspr_left)
+                        .unwrap_or_revert()
+                        .checked_div(scspr_tokens_amount)
+                        .unwrap_or_revert(),
+                    scspr_left,
+                ),
+                (0.into(), donation),
+            )
+        };
+        let () = runtime::call_versioned_contract(
+            data::pair_wise().into_hash().unwrap_or_revert().into(),
+            None,
+            "sync",
+            runtime_args! {},
+        );
+        amounts
+    }
+
+    fn _transfer_to_pair(&self, token: Key, amount: U256) {
+        if amount == U256::from(0) {
+            return;
+        }
+        let ret: Result<(), u32> = runtime::call_versioned_contract(
+            token.into_hash().unwrap_or_revert().into(),
+            None,
+            "transfer",
+            runtime_args! {
+                "recipient" => data::pair_wise(),
+                "amount" => amount
+            },
+        );
+        if ret.is_err() {
+            runtime::revert(ApiError::from(Error::PoolPriceOutOfRange));
+        }
+    }
+
+    // Reverts when pair_wise was seeded at a ratio further than PRICE_TOLERANCE percent from ours
+    fn check_pool_price(&self, wise_tokens_amount: U256, scspr_tokens_amount: U256) {
+        let (reserve_wise, reserve_scspr) = self.pool_reserves();
+        if reserve_wise == U256::from(0) && reserve_scspr == U256::from(0) {
+            return;
+        }
+        let pool: U256 = reserve_wise
+            .checked_mul(scspr_tokens_amount)
+            .unwrap_or_revert();
+        let intended: U256 = wise_tokens_amount
+            .checked_mul(reserve_scspr)
+            .unwrap_or_revert();
+        let difference: U256 = if pool > intended {
+            pool - intended
+        } else {
+            intended - pool
+        };
+        if difference.checked_mul(100.into()).unwrap_or_revert()
+            > intended
+                .checked_mul(data::price_tolerance())
+                .unwrap_or_revert()
+        {
+            runtime::revert(ApiError::from(Error::PoolPriceOutOfRange));
+        }
+    }
+
     fn get_my_tokens(&self) {
         self.after_uniswap_transfer();
         self.payout_investor_address(self.get_caller());
     }
 
+    fn get_my_tokens_and_stake(&mut self, lock_days: u64, referrer: Key) -> Vec<u32> {
+        self.after_uniswap_transfer();
+        let investor_address: Key = self.get_caller();
+        let payout: U256 = self._take_payout(investor_address);
+        if payout == U256::from(0) {
+            runtime::revert(ApiError::from(Error::NothingToStake));
+        }
+        let () = runtime::call_versioned_contract(
+            data::wise().into_hash().unwrap_or_revert().into(),
+            None,
+            "mint_supply",
+            runtime_args! {
+                "investor_address" => data::package(),
+                "amount" => payout
+            },
+        );
+        let (stake_id, _, _): (Vec<u32>, U256, Vec<u32>) = runtime::call_versioned_contract(
+            data::wise().into_hash().unwrap_or_revert().into(),
+            None,
+            "create_stake_for",
+            runtime_args! {
+                "staker" => investor_address,
+                "staked_amount" => payout,
+                "lock_days" => lock_days,
+                "referrer" => referrer
+            },
+        );
+        let mut stakes: Vec<(Vec<u32>, U256)> = PayoutStakes::instance().get(&investor_address);
+        stakes.push((stake_id.clone(), payout));
+        PayoutStakes::instance().set(&investor_address, stakes);
+        self.emit(&LiquidityTransformerEvent::PayoutStaked {
+            investor_address,
+            payout,
+            stake_id: stake_id.clone(),
+            lock_days,
+            referrer,
+        });
+        stake_id
+    }
+
     fn payout_investor_address(&self, investor_address: Key) -> U256 {
         self.after_uniswap_transfer();
-        let payout: U256 = PurchasedTokens::instance().get(&investor_address);
-        PurchasedTokens::instance().set(&investor_address, 0.into());
+        let payout: U256 = self._take_payout(investor_address);
         if payout > U256::from(0) {
             let () = runtime::call_versioned_contract(
                 data::wise().into_hash().unwrap_or_revert().into(),
@@ -483,8 +2291,192 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         payout
     }
 
+    fn claim_lp(&mut self) -> U256 {
+        self.after_uniswap_transfer();
+        if data::lp_destination() != LP_DESTINATION_INVESTORS {
+            runtime::revert(ApiError::from(Error::LpDistributionDisabled));
+        }
+        let investor_address: Key = self.get_caller();
+        if LpClaimed::instance().get(&investor_address) {
+            runtime::revert(ApiError::from(Error::LpAlreadyClaimed));
+        }
+        let amount: U256 = self.claimable_lp(investor_address);
+        LpClaimed::instance().set(&investor_address, true);
+        let lp_claimed_total: U256 = data::Globals::instance().get(LP_CLAIMED_TOTAL);
+        data::Globals::instance().set(
+            LP_CLAIMED_TOTAL,
+            lp_claimed_total.checked_add(amount).unwrap_or_revert(),
+        );
+        if amount > U256::from(0) {
+            let ret: Result<(), u32> = runtime::call_versioned_contract(
+                data::pair_wise().into_hash().unwrap_or_revert().into(),
+                None,
+                "transfer",
+                runtime_args! {
+                    "recipient" => investor_address,
+                    "amount" => amount
+                },
+            );
+            if ret.is_err() {
+                runtime::revert(ApiError::from(Error::LpTransferFailed));
+            }
+        }
+        self.emit(&LiquidityTransformerEvent::LpClaimed {
+            investor_address,
+            amount,
+        });
+        amount
+    }
+
+    fn claimable_lp(&self, investor_address: Key) -> U256 {
+        let total_cspr_contributed: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        if data::lp_destination() != LP_DESTINATION_INVESTORS
+            || total_cspr_contributed == U256::from(0)
+            || LpClaimed::instance().get(&investor_address)
+        {
+            return 0.into();
+        }
+        let lp_total: U256 = data::Globals::instance().get(LP_TOTAL);
+        lp_total
+            .checked_mul(InvestorBalance::instance().get(&investor_address))
+            .unwrap_or_revert()
+            .checked_div(total_cspr_contributed)
+            .unwrap_or_revert()
+    }
+
+    fn lp_totals(&self) -> (U256, U256) {
+        let globals = data::Globals::instance();
+        (globals.get(LP_TOTAL), globals.get(LP_CLAIMED_TOTAL))
+    }
+
+    fn _take_payout(&self, investor_address: Key) -> U256 {
+        if data::sale_mode() == SALE_MODE_DAILY {
+            self.settle_investor(investor_address);
+        }
+        let unlock_day: u64 = UnlockDays::instance().get(&investor_address);
+        if unlock_day > 0 && self.current_stakeable_day() < unlock_day {
+            runtime::revert(ApiError::from(Error::TokensLocked));
+        }
+        let payout: U256 = PurchasedTokens::instance()
+            .get(&investor_address)
+            .checked_add(ReferralTokens::instance().get(&investor_address))
+            .unwrap_or_revert()
+            .checked_add(BonusTokens::instance().get(&investor_address))
+            .unwrap_or_revert();
+        PurchasedTokens::instance().set(&investor_address, 0.into());
+        ReferralTokens::instance().set(&investor_address, 0.into());
+        BonusTokens::instance().set(&investor_address, 0.into());
+        if data::vesting_days() == 0 {
+            return payout;
+        }
+
+        // Vesting: collected tokens are released linearly from the liquidity day
+        let total: U256 = VestingTotals::instance()
+            .get(&investor_address)
+            .checked_add(payout)
+            .unwrap_or_revert();
+        VestingTotals::instance().set(&investor_address, total);
+        let claimed: U256 = VestingClaimed::instance().get(&investor_address);
+        let vested: U256 = self._vested_amount(total);
+        VestingClaimed::instance().set(&investor_address, vested);
+        vested.checked_sub(claimed).unwrap_or_revert()
+    }
+
+    fn _vested_amount(&self, total: U256) -> U256 {
+        let vesting_days: u64 = data::vesting_days();
+        if vesting_days == 0 {
+            return total;
+        }
+        let liquidity_day: u64 = data::Globals::instance().get(LIQUIDITY_DAY);
+        let elapsed: u64 = self.current_stakeable_day().saturating_sub(liquidity_day);
+        if elapsed < data::vesting_cliff_days() {
+            return 0.into();
+        }
+        if elapsed >= vesting_days {
+            return total;
+        }
+        total
+            .checked_mul(elapsed.into())
+            .unwrap_or_revert()
+            .checked_div(vesting_days.into())
+            .unwrap_or_revert()
+    }
+
+    fn claimable(&self, investor_address: Key) -> U256 {
+        if !data::Globals::instance().get::<bool>(UNISWAP_SWAPED) {
+            return 0.into();
+        }
+        let total: U256 = VestingTotals::instance()
+            .get(&investor_address)
+            .checked_add(PurchasedTokens::instance().get(&investor_address))
+            .unwrap_or_revert()
+            .checked_add(ReferralTokens::instance().get(&investor_address))
+            .unwrap_or_revert()
+            .checked_add(BonusTokens::instance().get(&investor_address))
+            .unwrap_or_revert();
+        self._vested_amount(total)
+            .checked_sub(VestingClaimed::instance().get(&investor_address))
+            .unwrap_or_revert()
+    }
+
+    fn payout_investors_batch(&mut self, start: Option<U256>, count: U256) -> U256 {
+        self.after_uniswap_transfer();
+        let current_stakeable_day: u64 = self.current_stakeable_day();
+        let cursor: U256 = data::Globals::instance().get(PAYOUT_CURSOR);
+        let start: U256 = start.unwrap_or(cursor);
+        // Referrer slots follow the investor slots
+        let investor_count: U256 = data::Globals::instance().get(INVESTOR_COUNT);
+        let slots: U256 = investor_count
+            .checked_add(ReferrerRegistry::instance().slots())
+            .unwrap_or_revert();
+        let end: U256 = start.saturating_add(count).min(slots);
+
+        let mut investors_paid: U256 = 0.into();
+        let mut tokens_paid: U256 = 0.into();
+        let mut index: U256 = start;
+        while index < end {
+            let investor_address: Key = if index < investor_count {
+                InvestorRegistry::instance().get(&index)
+            } else {
+                ReferrerRegistry::instance().get(&(index - investor_count))
+            };
+            // Locked investors are skipped, they claim with get_my_tokens once unlocked
+            let payout: U256 =
+                if current_stakeable_day < UnlockDays::instance().get(&investor_address) {
+                    0.into()
+                } else {
+                    self.payout_investor_address(investor_address)
+                };
+            if payout > U256::from(0) {
+                investors_paid = investors_paid.checked_add(1.into()).unwrap_or_revert();
+                tokens_paid = tokens_paid.checked_add(payout).unwrap_or_revert();
+            }
+            index = index.checked_add(1.into()).unwrap_or_revert();
+        }
+
+        // Only a batch resuming from the cursor moves it, an explicit start cannot skip investors
+        if start == cursor {
+            data::Globals::instance().set(PAYOUT_CURSOR, end);
+        }
+
+        self.emit(&LiquidityTransformerEvent::PayoutBatch {
+            start,
+            end,
+            investors_paid,
+            tokens_paid,
+        });
+        tokens_paid
+    }
+
+    fn payout_cursor(&self) -> U256 {
+        data::Globals::instance().get(PAYOUT_CURSOR)
+    }
+
     fn prepare_path(&self, token_address: Key) -> Vec<Key> {
-        vec![token_address, data::wcspr()]
+        let mut path: Vec<Key> = vec![token_address];
+        path.extend(SwapRoutes::instance().get(&token_address));
+        path.push(data::wcspr());
+        path
     }
 
     fn current_stakeable_day(&self) -> u64 {
@@ -497,23 +2489,49 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
     }
 
     fn request_refund(&mut self, caller_purse: URef) -> (U256, U256) {
+        if data::sale_mode() == SALE_MODE_DAILY {
+            self.settle_investor(self.get_caller());
+        }
         let ret: bool = data::Globals::instance().get(UNISWAP_SWAPED);
+        // A failed or cancelled sale refunds right away instead of waiting for forward_liquidity
+        let sale_failed: bool = self.sale_failed();
+        let sale_cancelled: bool = data::Globals::instance().get(SALE_CANCELLED);
         if ret
             || InvestorBalance::instance().get(&self.get_caller()) <= U256::from(0)
             || PurchasedTokens::instance().get(&self.get_caller()) <= U256::from(0)
-            || self.current_stakeable_day() <= (data::INVESTMENT_DAYS + 10) as u64
+            || (!sale_failed
+                && !sale_cancelled
+                && self.current_stakeable_day() <= data::investment_days() as u64 + 10)
         {
             runtime::revert(ApiError::from(Error::RefundNotPossible));
         }
-        let amount: U256 = InvestorBalance::instance().get(&self.get_caller());
+        // Covers a failure nobody marked before the first refund
+        if sale_failed && !data::Globals::instance().get::<bool>(SALE_FAILED) {
+            self._mark_sale_failed();
+        }
+        // Cash back already paid out of the contributions is not refunded twice
+        let balance: U256 = InvestorBalance::instance().get(&self.get_caller());
+        let amount: U256 = balance.saturating_sub(CashBackPaid::instance().get(&self.get_caller()));
         InvestorBalance::instance().set(&self.get_caller(), 0.into());
+        CashBackPaid::instance().set(&self.get_caller(), 0.into());
         let tokens: U256 = PurchasedTokens::instance().get(&self.get_caller());
         PurchasedTokens::instance().set(&self.get_caller(), 0.into());
+        BonusTokens::instance().set(&self.get_caller(), 0.into());
         let ret: U256 = data::Globals::instance().get(TOTAL_TRANSFER_TOKENS);
         data::Globals::instance().set(
             TOTAL_TRANSFER_TOKENS,
             ret.checked_sub(tokens).unwrap_or_revert(),
         );
+        let ret: U256 = data::Globals::instance().get(TOTAL_CSPR_CONTRIBUTED);
+        data::Globals::instance().set(
+            TOTAL_CSPR_CONTRIBUTED,
+            ret.checked_sub(balance).unwrap_or_revert(),
+        );
+        let ret: U256 = data::Globals::instance().get(REFUNDED_TOTAL);
+        data::Globals::instance().set(REFUNDED_TOTAL, ret.checked_add(amount).unwrap_or_revert());
+        // Referral credits, investment mode totals and accepted token caps go back in full
+        self._withdraw_investor_records(self.get_caller(), balance, balance);
+        self._mark_refunded(self.get_caller());
         if amount > U256::from(0) {
             system::transfer_from_purse_to_purse(
                 data::self_purse(),
@@ -530,6 +2548,198 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         (amount, tokens)
     }
 
+    fn _mark_refunded(&self, investor_address: Key) {
+        Refunded::instance().set(&investor_address, true);
+        let ret: U256 = data::Globals::instance().get(REFUNDED_INVESTORS);
+        data::Globals::instance().set(
+            REFUNDED_INVESTORS,
+            ret.checked_add(1.into()).unwrap_or_revert(),
+        );
+        let ret: U256 = data::Globals::instance().get(ACTIVE_INVESTORS);
+        data::Globals::instance().set(
+            ACTIVE_INVESTORS,
+            ret.checked_sub(1.into()).unwrap_or_revert(),
+        );
+    }
+
+    fn refunded(&self, investor_address: Key) -> bool {
+        Refunded::instance().get(&investor_address)
+    }
+
+    // Recomputes the totals from the investor dictionaries one page of registry slots at a
+    // time, the totals of the previous pages are passed back in and the page reaching the
+    // last slot compares them with Globals
+    #[allow(clippy::type_complexity)]
+    fn check_invariants(
+        &self,
+        offset: U256,
+        limit: U256,
+        totals: ((U256, U256, U256), (U256, U256)),
+    ) -> (((U256, U256, U256), (U256, U256)), Option<bool>) {
+        let globals = data::Globals::instance();
+        let registry = InvestorRegistry::instance();
+        let slots: U256 = registry.slots();
+        let end: U256 = offset.saturating_add(limit).min(slots);
+        let (
+            (mut total_cspr_contributed, mut total_transfer_tokens, mut referral_tokens_total),
+            (mut unique_investors, mut active_investors),
+        ) = totals;
+        let mut index: U256 = offset;
+        while index < end {
+            let investor_address: Key = registry.get(&index);
+            if !registry.is_canonical(&index, &investor_address) {
+                index = index.checked_add(1.into()).unwrap_or_revert();
+                continue;
+            }
+            unique_investors = unique_investors.checked_add(1.into()).unwrap_or_revert();
+            let balance: U256 = InvestorBalance::instance().get(&investor_address);
+            total_cspr_contributed = total_cspr_contributed
+                .checked_add(balance)
+                .unwrap_or_revert();
+            total_transfer_tokens = total_transfer_tokens
+                .checked_add(PurchasedTokens::instance().get(&investor_address))
+                .unwrap_or_revert();
+            for (_, tokens) in InvestorReferrals::instance().get(&investor_address) {
+                referral_tokens_total =
+                    referral_tokens_total.checked_add(tokens).unwrap_or_revert();
+            }
+            if balance > U256::from(0) {
+                active_investors = active_investors.checked_add(1.into()).unwrap_or_revert();
+            }
+            index = index.checked_add(1.into()).unwrap_or_revert();
+        }
+        let totals: ((U256, U256, U256), (U256, U256)) = (
+            (
+                total_cspr_contributed,
+                total_transfer_tokens,
+                referral_tokens_total,
+            ),
+            (unique_investors, active_investors),
+        );
+        if end < slots {
+            return (totals, None);
+        }
+
+        let mut consistent: bool = total_cspr_contributed
+            == globals.get::<U256>(TOTAL_CSPR_CONTRIBUTED)
+            && unique_investors == globals.get::<U256>(UNIQUE_INVESTOR_COUNT)
+            && active_investors == globals.get::<U256>(ACTIVE_INVESTORS);
+        // Purchased and referral tokens are zeroed by payouts and settled late in daily mode
+        if !globals.get::<bool>(UNISWAP_SWAPED) && data::sale_mode() != SALE_MODE_DAILY {
+            consistent = consistent
+                && total_transfer_tokens == globals.get::<U256>(TOTAL_TRANSFER_TOKENS)
+                && referral_tokens_total == globals.get::<U256>(REFERRAL_TOKENS_TOTAL);
+        }
+        (totals, Some(consistent))
+    }
+
+    fn investment_mode(&self, investment_mode: u8) -> (U256, U256, u64) {
+        InvestmentModes::instance().get(investment_mode)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn investment_mode_totals(&self, investment_mode: u8) -> ((U256, U256), (U256, U256)) {
+        InvestmentModeTotals::instance().get(investment_mode)
+    }
+
+    fn bonus_tokens(&self, investor_address: Key) -> U256 {
+        BonusTokens::instance().get(&investor_address)
+    }
+
+    fn referral_tokens(&self, referral_address: Key) -> U256 {
+        ReferralTokens::instance().get(&referral_address)
+    }
+
+    fn payout_stakes(&self, investor_address: Key) -> Vec<(Vec<u32>, U256)> {
+        PayoutStakes::instance().get(&investor_address)
+    }
+
+    fn investor_balance(&self, investor_address: Key) -> U256 {
+        InvestorBalance::instance().get(&investor_address)
+    }
+
+    fn purchased_tokens(&self, investor_address: Key) -> U256 {
+        PurchasedTokens::instance().get(&investor_address)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn globals(&self) -> ((U256, U256), (U256, U256), bool) {
+        let globals = data::Globals::instance();
+        (
+            (globals.get(CASH_BACK_TOTAL), globals.get(INVESTOR_COUNT)),
+            (
+                globals.get(TOTAL_TRANSFER_TOKENS),
+                globals.get(TOTAL_CSPR_CONTRIBUTED),
+            ),
+            globals.get(UNISWAP_SWAPED),
+        )
+    }
+
+    // Pages over registry slots, duplicate slots are left out so a page may be shorter than limit
+    fn unique_investors(&self, offset: U256, limit: U256) -> Vec<Key> {
+        let registry = InvestorRegistry::instance();
+        let end: U256 = offset.saturating_add(limit).min(registry.slots());
+        let mut investors: Vec<Key> = Vec::new();
+        let mut index: U256 = offset;
+        while index < end {
+            let investor_address: Key = registry.get(&index);
+            if registry.is_canonical(&index, &investor_address) {
+                investors.push(investor_address);
+            }
+            index = index.checked_add(1.into()).unwrap_or_revert();
+        }
+        investors
+    }
+
+    fn investor_counts(&self) -> (U256, U256, U256) {
+        let globals = data::Globals::instance();
+        (
+            globals.get(INVESTOR_COUNT),
+            globals.get(UNIQUE_INVESTOR_COUNT),
+            globals.get(ACTIVE_INVESTORS),
+        )
+    }
+
+    fn referrer_count(&self) -> U256 {
+        ReferrerRegistry::instance().slots()
+    }
+
+    // Indexes slots written before the registry kept an address index, the first slot of an
+    // address becomes canonical and later duplicates are skipped by readers
+    fn migrate_investor_registry(&mut self, count: U256) -> U256 {
+        self.only_keeper();
+        let globals = data::Globals::instance();
+        if globals.get::<bool>(REGISTRY_MIGRATED) {
+            runtime::revert(ApiError::from(Error::RegistryAlreadyMigrated));
+        }
+        let registry = InvestorRegistry::instance();
+        let cursor: U256 = globals.get(REGISTRY_CURSOR);
+        let end: U256 = cursor.saturating_add(count).min(registry.slots());
+        let mut unique_investors: U256 = globals.get(UNIQUE_INVESTOR_COUNT);
+        let mut active_investors: U256 = globals.get(ACTIVE_INVESTORS);
+        let mut migrated: U256 = 0.into();
+        let mut index: U256 = cursor;
+        while index < end {
+            let investor_address: Key = registry.get(&index);
+            if registry.index_of(&investor_address).is_none() {
+                registry.set_index(&investor_address, index);
+                unique_investors = unique_investors.checked_add(1.into()).unwrap_or_revert();
+                if InvestorBalance::instance().get(&investor_address) > U256::from(0) {
+                    active_investors = active_investors.checked_add(1.into()).unwrap_or_revert();
+                }
+                migrated = migrated.checked_add(1.into()).unwrap_or_revert();
+            }
+            index = index.checked_add(1.into()).unwrap_or_revert();
+        }
+        globals.set(UNIQUE_INVESTOR_COUNT, unique_investors);
+        globals.set(ACTIVE_INVESTORS, active_investors);
+        globals.set(REGISTRY_CURSOR, end);
+        if end == registry.slots() {
+            globals.set(REGISTRY_MIGRATED, true);
+        }
+        migrated
+    }
+
     fn fund_contract(&mut self, purse: URef, amount: U512) {
         system::transfer_from_purse_to_purse(purse, data::self_purse(), amount, None)
             .unwrap_or_revert();
@@ -543,25 +2753,37 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
         match liquidity_transformer_event {
             LiquidityTransformerEvent::WiseReservation {
                 sender_address,
+                beneficiary_address,
                 investment_amount,
                 token_amount,
                 current_stakeable_day,
                 investment_mode,
+                referral_address,
             } => {
                 let mut event = BTreeMap::new();
                 event.insert("contract_package_hash", package_hash);
                 event.insert("event_type", liquidity_transformer_event.type_name());
                 event.insert("sender_address", sender_address.to_string());
+                event.insert("beneficiary_address", beneficiary_address.to_string());
                 event.insert("investment_amount", investment_amount.to_string());
                 event.insert("token_amount", token_amount.to_string());
                 event.insert("current_stakeable_day", current_stakeable_day.to_string());
                 event.insert("investment_mode", investment_mode.to_string());
+                event.insert(
+                    "referral_address",
+                    referral_address
+                        .map(|referral_address| referral_address.to_string())
+                        .unwrap_or_default(),
+                );
                 events.push(event);
             }
             LiquidityTransformerEvent::UniswapSwapResult {
                 amount_token_a,
                 amount_token_b,
                 liquidity,
+                lp_destination,
+                lp_recipient,
+                unlock_day,
             } => {
                 let mut event = BTreeMap::new();
                 event.insert("contract_package_hash", package_hash);
@@ -569,6 +2791,9 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
                 event.insert("amount_token_a", amount_token_a.to_string());
                 event.insert("amount_token_b", amount_token_b.to_string());
                 event.insert("liquidity", liquidity.to_string());
+                event.insert("lp_destination", lp_destination.to_string());
+                event.insert("lp_recipient", lp_recipient.to_string());
+                event.insert("unlock_day", unlock_day.to_string());
                 events.push(event);
             }
             LiquidityTransformerEvent::CashBackIssued {
@@ -595,6 +2820,103 @@ pub trait LIQUIDITYTRANSFORMER<Storage: ContractStorage>: ContractContext<Storag
                 event.insert("refund_amount", refund_amount.to_string());
                 events.push(event);
             }
+            LiquidityTransformerEvent::PayoutBatch {
+                start,
+                end,
+                investors_paid,
+                tokens_paid,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("start", start.to_string());
+                event.insert("end", end.to_string());
+                event.insert("investors_paid", investors_paid.to_string());
+                event.insert("tokens_paid", tokens_paid.to_string());
+                events.push(event);
+            }
+            LiquidityTransformerEvent::PayoutStaked {
+                investor_address,
+                payout,
+                stake_id,
+                lock_days,
+                referrer,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("investor_address", investor_address.to_string());
+                event.insert("payout", payout.to_string());
+                event.insert("stake_id", format!("{:?}", stake_id));
+                event.insert("lock_days", lock_days.to_string());
+                event.insert("referrer", referrer.to_string());
+                events.push(event);
+            }
+            LiquidityTransformerEvent::LpClaimed {
+                investor_address,
+                amount,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("investor_address", investor_address.to_string());
+                event.insert("amount", amount.to_string());
+                events.push(event);
+            }
+            LiquidityTransformerEvent::SaleFailed {
+                total_cspr_contributed,
+                soft_cap,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("total_cspr_contributed", total_cspr_contributed.to_string());
+                event.insert("soft_cap", soft_cap.to_string());
+                events.push(event);
+            }
+            LiquidityTransformerEvent::SaleCancelled {
+                total_cspr_contributed,
+                cash_back_total,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("total_cspr_contributed", total_cspr_contributed.to_string());
+                event.insert("cash_back_total", cash_back_total.to_string());
+                events.push(event);
+            }
+            LiquidityTransformerEvent::SaleClosed {
+                close_day,
+                total_cspr_contributed,
+                total_transfer_tokens,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("close_day", close_day.to_string());
+                event.insert("total_cspr_contributed", total_cspr_contributed.to_string());
+                event.insert("total_transfer_tokens", total_transfer_tokens.to_string());
+                events.push(event);
+            }
+            LiquidityTransformerEvent::ReservationWithdrawn {
+                investor_address,
+                amount,
+                token_amount,
+                penalty,
+                cash_back_deducted,
+                payout,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", liquidity_transformer_event.type_name());
+                event.insert("investor_address", investor_address.to_string());
+                event.insert("amount", amount.to_string());
+                event.insert("token_amount", token_amount.to_string());
+                event.insert("penalty", penalty.to_string());
+                event.insert("cash_back_deducted", cash_back_deducted.to_string());
+                event.insert("payout", payout.to_string());
+                events.push(event);
+            }
         };
         for event in events {
             let _: URef = storage::new_uref(event);
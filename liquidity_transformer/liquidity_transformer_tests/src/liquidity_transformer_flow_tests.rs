@@ -1,5 +1,7 @@
 use crate::liquidity_transformer_instance::*;
-use casper_types::{runtime_args, Key, RuntimeArgs, U256};
+use casper_types::{runtime_args, Key, PublicKey, RuntimeArgs, SecretKey, U256};
+use liquidity_transformer_allowlist::MerkleTree;
+use num_traits::cast::AsPrimitive;
 
 #[test]
 fn test_reserve_claim_flow() {
@@ -14,6 +16,10 @@ fn test_reserve_claim_flow() {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => TWOTHOUSEND_CSPR
         },
         now(),
@@ -25,6 +31,10 @@ fn test_reserve_claim_flow() {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => TWOTHOUSEND_CSPR + TWOTHOUSEND_CSPR
         },
         now(),
@@ -36,6 +46,10 @@ fn test_reserve_claim_flow() {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => TWOTHOUSEND_CSPR * 3
         },
         now(),
@@ -88,3 +102,1986 @@ fn test_reserve_claim_flow() {
         "Tokens not transfered to owner"
     );
 }
+
+#[test]
+fn test_reserve_batch_payout_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    let users = [owner, env.next_user(), env.next_user()];
+    for user in users {
+        session_code_call(
+            &env,
+            user,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        );
+    }
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    // First batch pays the first two investors
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investors_batch",
+            "start" => Some(U256::from(0)),
+            "count" => U256::from(2)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "payout_investors_batch");
+    assert_eq!(
+        ret,
+        5280004000000000u64.into(),
+        "Invalid first batch payout"
+    );
+    let cursor: U256 = liquidity_transformer
+        .query_dictionary("globals", "payout_cursor".into())
+        .unwrap_or_default();
+    assert_eq!(cursor, 2.into(), "Cursor not advanced");
+
+    // Second batch resumes from the stored cursor
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investors_batch",
+            "start" => None::<U256>,
+            "count" => U256::from(2)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "payout_investors_batch");
+    assert_eq!(
+        ret,
+        2640002000000000u64.into(),
+        "Invalid second batch payout"
+    );
+
+    for user in users {
+        let balance: U256 = wise
+            .query_dictionary("balances", key_to_str(&Key::Account(user)))
+            .unwrap_or_default();
+        assert_eq!(
+            balance,
+            2640002000000000u64.into(), // calculated amount in contract
+            "Tokens not transfered to investor"
+        );
+    }
+}
+
+#[test]
+fn test_referral_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    let (user, referrer) = (env.next_user(), env.next_user());
+    liquidity_transformer.call_contract(
+        owner,
+        "set_referral_bonus",
+        runtime_args! {
+            "referral_bonus" => U256::from(10)
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => Some(Key::Account(referrer)),
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "referral_tokens",
+            "referral_address" => Key::Account(referrer)
+        },
+        now(),
+    );
+    let ret: U256 = session_code_result(&env, owner, "referral_tokens");
+    assert_eq!(ret, 264000200000000u64.into(), "Invalid referral tokens"); // 10% of purchased tokens
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    liquidity_transformer.call_contract(user, "get_my_tokens", runtime_args! {}, time);
+    liquidity_transformer.call_contract(referrer, "get_my_tokens", runtime_args! {}, time);
+
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        2640002000000000u64.into(), // calculated amount in contract
+        "Tokens not transfered to investor"
+    );
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(referrer)))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        264000200000000u64.into(), // calculated amount in contract
+        "Referral tokens not transfered to referrer"
+    );
+}
+
+#[test]
+fn test_investment_mode_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    let user = env.next_user();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investment_mode",
+        runtime_args! {
+            "investment_mode" => 2_u8,
+            "cash_back_rate" => U256::from(0),
+            "token_bonus" => U256::from(10),
+            "lock_days" => 0_u64
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 2_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "investment_mode_totals",
+            "investment_mode" => 2_u8
+        },
+        now(),
+    );
+    let ret: ((U256, U256), (U256, U256)) =
+        session_code_result(&env, owner, "investment_mode_totals");
+    assert_eq!(
+        ret,
+        (
+            (
+                <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+                2640002000000000u64.into()
+            ),
+            (0.into(), 264000200000000u64.into())
+        ),
+        "Invalid investment mode totals"
+    );
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    liquidity_transformer.call_contract(user, "get_my_tokens", runtime_args! {}, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        2904002200000000u64.into(), // purchased tokens plus 10% bonus
+        "Bonus tokens not transfered to investor"
+    );
+}
+
+#[test]
+fn test_reserve_claim_and_stake_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) =
+        deploy_with_stakeable_double();
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let payout: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert!(payout > 0.into(), "Nothing purchased");
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    liquidity_transformer.call_contract(
+        user,
+        "get_my_tokens_and_stake",
+        runtime_args! {
+            "lock_days" => 30_u64,
+            "referrer" => Key::Account(owner)
+        },
+        time,
+    );
+    let purchased_tokens: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(purchased_tokens, 0.into(), "Purchased tokens not staked");
+    let balance: U256 = wise
+        .query_dictionary(
+            "balances",
+            key_to_str(&Key::Hash(liquidity_transformer.package_hash())),
+        )
+        .unwrap_or_default();
+    assert_eq!(balance, 0.into(), "Minted tokens left in transformer");
+
+    // The stake is owned by the investor with the requested lock and referrer
+    let stakes: Vec<(Vec<u32>, U256, (u64, Key))> = wise
+        .query_dictionary("stakes", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(stakes.len(), 1, "Stake not created for the investor");
+    let (stake_id, staked_amount, (lock_days, referrer)) = stakes[0].clone();
+    assert_eq!(staked_amount, payout);
+    assert_eq!(lock_days, 30);
+    assert_eq!(referrer, Key::Account(owner));
+
+    // The payout is linked to that stake, as in the PayoutStaked event
+    let payout_stakes: Vec<(Vec<u32>, U256)> = liquidity_transformer
+        .query_dictionary("payout_stakes", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(payout_stakes, vec![(stake_id, payout)]);
+}
+
+#[test]
+fn test_vesting_flow() {
+    const VESTING_DAYS: u64 = 10;
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) =
+        deploy_with_vesting(2, VESTING_DAYS);
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    // Nothing vested before the cliff
+    liquidity_transformer.call_contract(user, "get_my_tokens", runtime_args! {}, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(balance, 0.into(), "Tokens released before cliff");
+
+    // Half of the tokens vested after half of the vesting days
+    let time = time + (VESTING_DAYS / 2) * MILLI_SECONDS_IN_DAY;
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "claimable",
+            "investor_address" => Key::Account(user)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "claimable");
+    assert_eq!(ret, 1320001000000000u64.into(), "Invalid claimable amount");
+    liquidity_transformer.call_contract(user, "get_my_tokens", runtime_args! {}, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        1320001000000000u64.into(),
+        "Vested tokens not released"
+    );
+
+    // Everything vested after the vesting days
+    let time = time + VESTING_DAYS * MILLI_SECONDS_IN_DAY;
+    liquidity_transformer.call_contract(user, "get_my_tokens", runtime_args! {}, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        2640002000000000u64.into(), // calculated amount in contract
+        "Vested tokens not released"
+    );
+}
+
+#[test]
+fn test_forward_liquidity_pre_seeded_pool_in_tolerance_flow() {
+    let (env, liquidity_transformer, owner, _, _, router, _, wise, scspr, _, pair_wise, _, _, time) =
+        deploy_with_stakeable_double();
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let wise_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_transfer_tokens".into())
+        .unwrap_or_default();
+    let scspr_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_cspr_contributed".into())
+        .unwrap_or_default();
+
+    // A tenth of our liquidity priced 0.5% above the sale price, inside the 1% tolerance
+    seed_pair_wise(
+        &env,
+        owner,
+        &wise,
+        &scspr,
+        &router,
+        &pair_wise,
+        wise_total / 10,
+        scspr_total * 201 / 2000,
+        time,
+    );
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    // The router takes less WISE than offered, but not below the 99% minimum
+    let (dust_wise, dust_scspr): (U256, U256) = (
+        liquidity_transformer
+            .query_dictionary("globals", "dust_wise".into())
+            .unwrap_or_default(),
+        liquidity_transformer
+            .query_dictionary("globals", "dust_scspr".into())
+            .unwrap_or_default(),
+    );
+    assert!(
+        dust_wise > 0.into(),
+        "Router took all WISE from a mispriced pool"
+    );
+    assert!(dust_wise * 100 <= wise_total, "WISE minimum not enforced");
+    assert_eq!(dust_scspr, 0.into(), "SCSPR left over");
+
+    let treasury = env.next_user();
+    liquidity_transformer.call_contract(
+        owner,
+        "sweep_dust",
+        runtime_args! {
+            "recipient" => Key::Account(treasury)
+        },
+        time,
+    );
+    let swept: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(treasury)))
+        .unwrap_or_default();
+    assert_eq!(swept, dust_wise, "Leftover WISE not swept");
+    let kept: U256 = wise
+        .query_dictionary(
+            "balances",
+            key_to_str(&Key::Hash(liquidity_transformer.package_hash())),
+        )
+        .unwrap_or_default();
+    assert_eq!(kept, 0.into(), "WISE left in transformer");
+    let dust_wise: U256 = liquidity_transformer
+        .query_dictionary("globals", "dust_wise".into())
+        .unwrap_or_default();
+    assert_eq!(dust_wise, 0.into(), "Leftover swept twice");
+}
+
+#[test]
+fn test_forward_liquidity_dust_pool_flow() {
+    let (env, liquidity_transformer, owner, _, _, router, _, wise, scspr, _, pair_wise, _, _, time) =
+        deploy_with_stakeable_double();
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let wise_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_transfer_tokens".into())
+        .unwrap_or_default();
+    let scspr_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_cspr_contributed".into())
+        .unwrap_or_default();
+
+    // Far from the sale price, but below 1/1000 of our liquidity on both sides
+    seed_pair_wise(
+        &env,
+        owner,
+        &wise,
+        &scspr,
+        &router,
+        &pair_wise,
+        U256::from(1_000_000),
+        U256::from(5_000_000),
+        time,
+    );
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    // The pool ends up at the sale price within the tolerance
+    let pool_wise: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Hash(pair_wise.package_hash())))
+        .unwrap_or_default();
+    let pool_scspr: U256 = scspr
+        .query_dictionary("balances", key_to_str(&Key::Hash(pair_wise.package_hash())))
+        .unwrap_or_default();
+    let (pool, intended) = (pool_wise * scspr_total, wise_total * pool_scspr);
+    let difference = if pool > intended {
+        pool - intended
+    } else {
+        intended - pool
+    };
+    assert!(difference * 100 <= intended, "Dust pool not aligned");
+
+    // Whatever was not added can be recovered by the keeper
+    let dust: (U256, U256) = (
+        liquidity_transformer
+            .query_dictionary("globals", "dust_wise".into())
+            .unwrap_or_default(),
+        liquidity_transformer
+            .query_dictionary("globals", "dust_scspr".into())
+            .unwrap_or_default(),
+    );
+    let treasury = env.next_user();
+    liquidity_transformer.call_contract(
+        owner,
+        "sweep_dust",
+        runtime_args! {
+            "recipient" => Key::Account(treasury)
+        },
+        time,
+    );
+    let swept: (U256, U256) = (
+        wise.query_dictionary("balances", key_to_str(&Key::Account(treasury)))
+            .unwrap_or_default(),
+        scspr
+            .query_dictionary("balances", key_to_str(&Key::Account(treasury)))
+            .unwrap_or_default(),
+    );
+    assert_eq!(swept, dust, "Leftover not swept");
+    for token in [&wise, &scspr] {
+        let kept: U256 = token
+            .query_dictionary(
+                "balances",
+                key_to_str(&Key::Hash(liquidity_transformer.package_hash())),
+            )
+            .unwrap_or_default();
+        assert_eq!(kept, 0.into(), "Tokens left in transformer");
+    }
+}
+
+#[test]
+fn test_forward_liquidity_to_lp_locker_flow() {
+    const UNLOCK_DAY: u64 = 30;
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, pair_wise, _, _, time) =
+        deploy();
+
+    let treasury = env.next_user();
+    let lp_locker = deploy_lp_locker(
+        &env,
+        owner,
+        Key::Hash(pair_wise.package_hash()),
+        Key::Account(treasury),
+        Key::Hash(wise.package_hash()),
+        UNLOCK_DAY,
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_lp_destination",
+        runtime_args! {
+            "lp_destination" => 2_u8,
+            "lp_recipient" => Key::Hash(lp_locker.package_hash())
+        },
+        time,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    let locked: U256 = pair_wise
+        .query_dictionary("balances", key_to_str(&Key::Hash(lp_locker.package_hash())))
+        .unwrap_or_default();
+    assert!(locked > 0.into(), "LP tokens not sent to locker");
+
+    // Stakeable launched two days before the sale, so day 30 is reached after 8 more days
+    let time = time + 10 * MILLI_SECONDS_IN_DAY;
+    lp_locker.call_contract(owner, "release", runtime_args! {}, time);
+    let released: U256 = pair_wise
+        .query_dictionary("balances", key_to_str(&Key::Account(treasury)))
+        .unwrap_or_default();
+    assert_eq!(released, locked, "LP tokens not released to beneficiary");
+}
+
+#[test]
+fn test_claim_lp_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, pair_wise, _, _, time) =
+        deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_lp_destination",
+        runtime_args! {
+            "lp_destination" => 3_u8,
+            "lp_recipient" => Key::Account(owner)
+        },
+        time,
+    );
+    let (user1, user2) = (env.next_user(), env.next_user());
+    for user in [user1, user2] {
+        session_code_call(
+            &env,
+            user,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        );
+    }
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "lp_totals"
+        },
+        time,
+    );
+    let (lp_total, lp_claimed_total): (U256, U256) = session_code_result(&env, owner, "lp_totals");
+    let kept: U256 = pair_wise
+        .query_dictionary(
+            "balances",
+            key_to_str(&Key::Hash(liquidity_transformer.package_hash())),
+        )
+        .unwrap_or_default();
+    assert_eq!(kept, lp_total, "LP tokens not kept in transformer");
+    assert_eq!(lp_claimed_total, 0.into(), "LP claimed before claim_lp");
+
+    // Both investors contributed the same amount so each owns half of the LP
+    liquidity_transformer.call_contract(user1, "claim_lp", runtime_args! {}, time);
+    let balance: U256 = pair_wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user1)))
+        .unwrap_or_default();
+    assert_eq!(balance, lp_total / 2, "Invalid LP share");
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "claimable_lp",
+            "investor_address" => Key::Account(user1)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "claimable_lp");
+    assert_eq!(ret, 0.into(), "LP claimable twice");
+}
+
+#[test]
+fn test_daily_sale_mode_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_sale_mode",
+        runtime_args! {
+            "sale_mode" => 1_u8
+        },
+        time,
+    );
+    let (user1, user2) = (env.next_user(), env.next_user());
+    for user in [user1, user2] {
+        session_code_call(
+            &env,
+            user,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        );
+    }
+    // Tokens are only allocated once the day is settled
+    let purchased: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(user1)))
+        .unwrap_or_default();
+    assert_eq!(purchased, 0.into(), "Tokens allocated before day closed");
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    // Both investors contributed the same amount on the same day
+    let daily_supply: U256 = MAX_SUPPLY / U256::from(INVESTMENT_DAYS);
+    liquidity_transformer.call_contract(user1, "get_my_tokens", runtime_args! {}, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(user1)))
+        .unwrap_or_default();
+    assert_eq!(balance, daily_supply / 2, "Invalid daily allocation");
+}
+
+#[test]
+fn test_bonding_curve_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_sale_mode",
+        runtime_args! {
+            "sale_mode" => 2_u8
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_bonding_curve",
+        runtime_args! {
+            "curve_type" => 0_u8,
+            "factor" => U256::from(1),
+            "step" => U256::from(0)
+        },
+        time,
+    );
+
+    let amount: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "quote_tokens",
+            "amount" => amount
+        },
+        now(),
+    );
+    let (quoted, refund): (U256, U256) = session_code_result(&env, owner, "quote_tokens");
+    assert_eq!(refund, 0.into(), "Refund quoted below max supply");
+    // The curve starts at TOKEN_COST so it sells less than the flat price
+    assert!(
+        quoted < amount / TOKEN_COST * 1_000_000_000,
+        "Curve price not above flat price"
+    );
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let purchased: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(purchased, quoted, "Purchase differs from quote");
+}
+
+#[test]
+fn test_soft_cap_refund_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let soft_cap: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR) * 2;
+    liquidity_transformer.call_contract(
+        owner,
+        "set_soft_cap",
+        runtime_args! {
+            "soft_cap" => soft_cap
+        },
+        time,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+
+    // Right after the investment days, without the usual 10 days wait
+    const TIME: u64 = 16 * MILLI_SECONDS_IN_DAY;
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "request_refund"
+        },
+        now() + TIME,
+    );
+    let (refund, _): (U256, U256) = session_code_result(&env, owner, "request_refund");
+    assert_eq!(
+        refund,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Invalid refund"
+    );
+}
+
+#[test]
+fn test_mark_sale_failed_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let soft_cap: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR) * 2;
+    liquidity_transformer.call_contract(
+        owner,
+        "set_soft_cap",
+        runtime_args! {
+            "soft_cap" => soft_cap
+        },
+        time,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+
+    // Anyone can record the failure before the first refund
+    const TIME: u64 = 16 * MILLI_SECONDS_IN_DAY;
+    let user = env.next_user();
+    liquidity_transformer.call_contract(user, "mark_sale_failed", runtime_args! {}, now() + TIME);
+    let failed: bool = liquidity_transformer
+        .query_dictionary("globals", "sale_failed".into())
+        .unwrap_or_default();
+    assert!(failed, "Sale failure not recorded");
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "request_refund"
+        },
+        now() + TIME,
+    );
+    let (refund, _): (U256, U256) = session_code_result(&env, owner, "request_refund");
+    assert_eq!(
+        refund,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Invalid refund"
+    );
+}
+
+#[test]
+fn test_cancel_sale_refund_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    liquidity_transformer.call_contract(owner, "cancel_sale", runtime_args! {}, time);
+    let cancelled: bool = liquidity_transformer
+        .query_dictionary("globals", "sale_cancelled".into())
+        .unwrap_or_default();
+    assert!(cancelled, "Sale not cancelled");
+
+    // Refund is available during the investment phase
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "request_refund"
+        },
+        now(),
+    );
+    let (refund, _): (U256, U256) = session_code_result(&env, user, "request_refund");
+    assert_eq!(
+        refund,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Invalid refund"
+    );
+}
+
+#[test]
+fn test_cancel_sale_daily_mode_refund_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_sale_mode",
+        runtime_args! {
+            "sale_mode" => 1_u8
+        },
+        time,
+    );
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    liquidity_transformer.call_contract(owner, "cancel_sale", runtime_args! {}, time);
+
+    // The reservation day is still open but the refund settles it
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "request_refund"
+        },
+        now(),
+    );
+    let (refund, tokens): (U256, U256) = session_code_result(&env, user, "request_refund");
+    assert_eq!(
+        refund,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Invalid refund"
+    );
+    assert_eq!(
+        tokens,
+        MAX_SUPPLY / U256::from(INVESTMENT_DAYS),
+        "Open day not settled"
+    );
+    let total_transfer_tokens: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_transfer_tokens".into())
+        .unwrap_or_default();
+    assert_eq!(total_transfer_tokens, 0.into(), "Tokens left after refund");
+}
+
+#[test]
+fn test_sale_closed_at_hard_cap_flow() {
+    let (_, liquidity_transformer, _, _, _) = sell_out_and_forward();
+
+    let swapped: bool = liquidity_transformer
+        .query_dictionary("globals", "uniswap_swaped".into())
+        .unwrap_or_default();
+    assert!(swapped, "Liquidity not forwarded after the close day");
+    let close_day: u64 = liquidity_transformer
+        .query_dictionary("globals", "close_day".into())
+        .unwrap_or_default();
+    assert!(close_day > 0, "Close day cleared after the swap");
+}
+
+#[test]
+fn test_withdraw_full_reservation_with_investor_minimum_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let two_thousand: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investor_limits",
+        runtime_args! {
+            "min_investment" => two_thousand / 2 + 1,
+            "max_investment" => U256::from(0),
+            "daily_cap" => U256::from(0)
+        },
+        time,
+    );
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        time,
+    );
+    // Leaving nothing behind is allowed whatever the minimum
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => two_thousand
+        },
+        time,
+    );
+    let balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(balance, 0.into(), "Reservation not withdrawn");
+}
+
+#[test]
+fn test_withdraw_reservation_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_withdraw_penalty",
+        runtime_args! {
+            "withdraw_penalty" => U256::from(10)
+        },
+        time,
+    );
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let purchased_before: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+
+    let amount: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR) / 2;
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => amount
+        },
+        now(),
+    );
+    let payout: U256 = session_code_result(&env, user, "withdraw_reservation");
+    assert_eq!(payout, amount - amount / 10, "Invalid payout");
+
+    let balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(balance, amount, "Invalid investor balance");
+    let purchased_after: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        purchased_after,
+        purchased_before - purchased_before / 2,
+        "Purchased tokens not reduced"
+    );
+    let penalty_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "withdraw_penalty_total".into())
+        .unwrap_or_default();
+    assert_eq!(penalty_total, amount / 10, "Invalid penalty total");
+}
+
+#[test]
+fn test_reserve_wise_for_beneficiary_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
+
+    let beneficiary = env.next_user();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investment_mode",
+        runtime_args! {
+            "investment_mode" => 2_u8,
+            "cash_back_rate" => U256::from(10),
+            "token_bonus" => U256::from(0),
+            "lock_days" => 0_u64
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 2_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => Some(Key::Account(beneficiary)),
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let beneficiary_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(beneficiary)))
+        .unwrap_or_default();
+    assert_eq!(
+        beneficiary_balance,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Beneficiary not credited"
+    );
+    let beneficiary_tokens: U256 = liquidity_transformer
+        .query_dictionary("purchased_tokens", key_to_str(&Key::Account(beneficiary)))
+        .unwrap_or_default();
+    assert!(
+        beneficiary_tokens > 0.into(),
+        "Beneficiary tokens not credited"
+    );
+    let owner_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(owner)))
+        .unwrap_or_default();
+    assert_eq!(owner_balance, 0.into(), "Payer credited");
+    // The cash back is paid to and deducted from the beneficiary
+    let beneficiary_cash_back: U256 = liquidity_transformer
+        .query_dictionary("cash_back_paid", key_to_str(&Key::Account(beneficiary)))
+        .unwrap_or_default();
+    assert!(
+        beneficiary_cash_back > 0.into(),
+        "Cash back not recorded for beneficiary"
+    );
+    let owner_cash_back: U256 = liquidity_transformer
+        .query_dictionary("cash_back_paid", key_to_str(&Key::Account(owner)))
+        .unwrap_or_default();
+    assert_eq!(owner_cash_back, 0.into(), "Cash back recorded for payer");
+}
+
+#[test]
+fn test_refund_keeps_totals_consistent_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let user = env.next_user();
+    for (investor, referral_address) in [(owner, None), (user, Some(Key::Account(owner)))] {
+        session_code_call(
+            &env,
+            investor,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => referral_address,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        );
+    }
+    liquidity_transformer.call_contract(owner, "cancel_sale", runtime_args! {}, time);
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "request_refund"
+        },
+        now(),
+    );
+
+    let total_cspr_contributed: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_cspr_contributed".into())
+        .unwrap_or_default();
+    assert_eq!(
+        total_cspr_contributed,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Refund not subtracted from total contributed"
+    );
+    let refunded_investors: U256 = liquidity_transformer
+        .query_dictionary("globals", "refunded_investors".into())
+        .unwrap_or_default();
+    assert_eq!(
+        refunded_investors,
+        1.into(),
+        "Refunded investor not tracked"
+    );
+
+    // The referral credit and the investment mode totals of the refund are taken back
+    let referral_tokens: U256 = liquidity_transformer
+        .query_dictionary("referral_tokens", key_to_str(&Key::Account(owner)))
+        .unwrap_or_default();
+    assert_eq!(referral_tokens, 0.into(), "Referral credit not reversed");
+    let referral_tokens_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "referral_tokens_total".into())
+        .unwrap_or_default();
+    assert_eq!(
+        referral_tokens_total,
+        0.into(),
+        "Referral total not reversed"
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "investment_mode_totals",
+            "investment_mode" => 1_u8
+        },
+        now(),
+    );
+    let ((mode_cspr, _), _): ((U256, U256), (U256, U256)) =
+        session_code_result(&env, owner, "investment_mode_totals");
+    assert_eq!(
+        mode_cspr, total_cspr_contributed,
+        "Investment mode total not reversed"
+    );
+
+    // One slot per page, the totals of the first page are passed to the second
+    let mut totals: ((U256, U256, U256), (U256, U256)) = Default::default();
+    let mut consistent: Option<bool> = None;
+    for offset in 0..2_u64 {
+        assert_eq!(consistent, None, "Verdict given before the last slot");
+        session_code_call(
+            &env,
+            owner,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "check_invariants",
+                "offset" => U256::from(offset),
+                "limit" => U256::from(1),
+                "totals" => totals
+            },
+            now(),
+        );
+        let ret: (((U256, U256, U256), (U256, U256)), Option<bool>) =
+            session_code_result(&env, owner, "check_invariants");
+        totals = ret.0;
+        consistent = ret.1;
+    }
+    let ((cspr, _, referral), (unique_investors, active_investors)) = totals;
+    assert_eq!(cspr, total_cspr_contributed, "Invalid recomputed total");
+    assert_eq!(referral, 0.into(), "Invalid recomputed referral total");
+    assert_eq!(unique_investors, 2.into(), "Invalid unique investors");
+    assert_eq!(active_investors, 1.into(), "Invalid active investors");
+    assert_eq!(
+        consistent,
+        Some(true),
+        "Globals drifted from investor dictionaries"
+    );
+}
+
+#[test]
+fn test_investor_registry_dedup_flow() {
+    let (env, liquidity_transformer, _, _, _, _, _, _, _, _, _, _, _, _) = deploy();
+
+    let user = env.next_user();
+    let reserve = |time: u64| {
+        session_code_call(
+            &env,
+            user,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            time,
+        );
+    };
+    reserve(now());
+    // Withdrawing everything leaves a zero balance, reserving again must not add a new slot
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR)
+        },
+        now(),
+    );
+    reserve(now());
+
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "investor_counts"
+        },
+        now(),
+    );
+    let counts: (U256, U256, U256) = session_code_result(&env, user, "investor_counts");
+    assert_eq!(
+        counts,
+        (1.into(), 1.into(), 1.into()),
+        "Investor registered twice"
+    );
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "unique_investors",
+            "offset" => U256::from(0),
+            "limit" => U256::from(10)
+        },
+        now(),
+    );
+    let investors: Vec<Key> = session_code_result(&env, user, "unique_investors");
+    assert_eq!(
+        investors,
+        vec![Key::Account(user)],
+        "Invalid investors page"
+    );
+}
+
+#[test]
+fn test_investor_limits_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let two_thousand: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investor_limits",
+        runtime_args! {
+            "min_investment" => U256::from(0),
+            "max_investment" => two_thousand * 2,
+            "daily_cap" => two_thousand + two_thousand / 2
+        },
+        time,
+    );
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+
+    // The daily cap is tighter than the remaining maximum today
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "remaining_allowance",
+            "investor_address" => Key::Account(user)
+        },
+        now(),
+    );
+    let allowance: U256 = session_code_result(&env, user, "remaining_allowance");
+    assert_eq!(allowance, two_thousand / 2, "Invalid daily allowance");
+
+    // On the next day only the maximum total applies
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "remaining_allowance",
+            "investor_address" => Key::Account(user)
+        },
+        now() + MILLI_SECONDS_IN_DAY,
+    );
+    let allowance: U256 = session_code_result(&env, user, "remaining_allowance");
+    assert_eq!(allowance, two_thousand, "Invalid total allowance");
+}
+
+#[test]
+fn test_kyc_reservation_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let kyc_secret = SecretKey::secp256k1_from_bytes([7u8; 32]).unwrap();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_kyc_signer",
+        runtime_args! {
+            "kyc_signer" => PublicKey::from(&kyc_secret)
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_compliance_mode",
+        runtime_args! {
+            "compliance_mode" => true
+        },
+        time,
+    );
+
+    let user = env.next_user();
+    let nonce: U256 = 1.into();
+    let kyc_approval = sign_kyc_approval(
+        &kyc_secret,
+        Key::Hash(liquidity_transformer.package_hash()),
+        Key::Account(user),
+        Key::Account(user),
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        now() + MILLI_SECONDS_IN_DAY,
+        nonce,
+    );
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => Some(kyc_approval),
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let investor_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        investor_balance,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Approved reservation not credited"
+    );
+    let nonce_used: bool = liquidity_transformer
+        .query_dictionary(
+            "kyc_nonces",
+            format!("{}_{}", key_to_str(&Key::Account(user)), nonce),
+        )
+        .unwrap_or_default();
+    assert!(nonce_used, "KYC nonce not consumed");
+}
+
+#[test]
+fn test_kyc_reservation_for_beneficiary_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let kyc_secret = SecretKey::secp256k1_from_bytes([7u8; 32]).unwrap();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_kyc_signer",
+        runtime_args! {
+            "kyc_signer" => PublicKey::from(&kyc_secret)
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_compliance_mode",
+        runtime_args! {
+            "compliance_mode" => true
+        },
+        time,
+    );
+
+    // The approval names the owner as caller and the user as credited investor
+    let user = env.next_user();
+    let nonce: U256 = 1.into();
+    let kyc_approval = sign_kyc_approval(
+        &kyc_secret,
+        Key::Hash(liquidity_transformer.package_hash()),
+        Key::Account(owner),
+        Key::Account(user),
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        now() + MILLI_SECONDS_IN_DAY,
+        nonce,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => Some(kyc_approval),
+            "beneficiary" => Some(Key::Account(user)),
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let investor_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        investor_balance,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Beneficiary not credited"
+    );
+    let nonce_used: bool = liquidity_transformer
+        .query_dictionary(
+            "kyc_nonces",
+            format!("{}_{}", key_to_str(&Key::Account(user)), nonce),
+        )
+        .unwrap_or_default();
+    assert!(nonce_used, "KYC nonce not consumed for the beneficiary");
+}
+
+#[test]
+fn test_allowlist_reservation_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    let user = env.next_user();
+    let cap: U256 = <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+    let csv: String = format!(
+        "address,cap\n{},{}\n{},{}\n{},{}\n",
+        Key::Account(owner).to_formatted_string(),
+        cap,
+        Key::Account(user).to_formatted_string(),
+        cap,
+        Key::Account(env.next_user()).to_formatted_string(),
+        cap * 2
+    );
+    let tree = MerkleTree::from_csv(csv.as_bytes()).unwrap();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_allowlist_root",
+        runtime_args! {
+            "allowlist_root" => Some(tree.root_hex())
+        },
+        time,
+    );
+
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => tree.proof_hex(&Key::Account(user)),
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let investor_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(
+        investor_balance, cap,
+        "Allowlisted reservation not credited"
+    );
+}
+
+#[test]
+fn test_withdraw_reservation_reverses_referral_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_withdraw_penalty",
+        runtime_args! {
+            "withdraw_penalty" => U256::from(10)
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_referral_bonus",
+        runtime_args! {
+            "referral_bonus" => U256::from(10)
+        },
+        time,
+    );
+    let user = env.next_user();
+    let referrer = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => Some(Key::Account(referrer)),
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let referral_tokens: U256 = liquidity_transformer
+        .query_dictionary("referral_tokens", key_to_str(&Key::Account(referrer)))
+        .unwrap_or_default();
+    assert!(referral_tokens > 0.into(), "Referrer not credited");
+
+    let amount: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => amount
+        },
+        now(),
+    );
+    let referral_tokens: U256 = liquidity_transformer
+        .query_dictionary("referral_tokens", key_to_str(&Key::Account(referrer)))
+        .unwrap_or_default();
+    assert_eq!(referral_tokens, 0.into(), "Referral tokens not reversed");
+    let referral_tokens_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "referral_tokens_total".into())
+        .unwrap_or_default();
+    assert_eq!(
+        referral_tokens_total,
+        0.into(),
+        "Referral total not reversed"
+    );
+    let ((mode_cspr, mode_tokens), _): ((U256, U256), (U256, U256)) = liquidity_transformer
+        .query_dictionary("investment_mode_totals", "1".into())
+        .unwrap_or_default();
+    assert_eq!(mode_cspr, 0.into(), "Mode contribution not reversed");
+    assert_eq!(mode_tokens, 0.into(), "Mode tokens not reversed");
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "sweep_withdraw_penalties"
+        },
+        now(),
+    );
+    let swept: U256 = session_code_result(&env, owner, "sweep_withdraw_penalties");
+    assert_eq!(swept, amount / 10, "Invalid swept penalty");
+    let swept_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "withdraw_penalty_swept".into())
+        .unwrap_or_default();
+    assert_eq!(swept_total, amount / 10, "Sweep not recorded");
+}
+
+#[test]
+fn test_payout_batch_explicit_start_keeps_cursor_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    let users = [owner, env.next_user(), env.next_user()];
+    for user in users {
+        session_code_call(
+            &env,
+            user,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        );
+    }
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    // A batch starting past the cursor pays its investors but leaves the cursor alone
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investors_batch",
+            "start" => Some(U256::from(1)),
+            "count" => U256::from(2)
+        },
+        time,
+    );
+    let cursor: U256 = liquidity_transformer
+        .query_dictionary("globals", "payout_cursor".into())
+        .unwrap_or_default();
+    assert_eq!(cursor, 0.into(), "Cursor jumped");
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investors_batch",
+            "start" => None::<U256>,
+            "count" => U256::from(3)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "payout_investors_batch");
+    assert_eq!(ret, 2640002000000000u64.into(), "First investor not paid");
+    let cursor: U256 = liquidity_transformer
+        .query_dictionary("globals", "payout_cursor".into())
+        .unwrap_or_default();
+    assert_eq!(cursor, 3.into(), "Cursor not advanced");
+}
+
+#[test]
+fn test_payout_batch_pays_referrer_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_referral_bonus",
+        runtime_args! {
+            "referral_bonus" => U256::from(10)
+        },
+        time,
+    );
+    // The referrer never reserves itself
+    let referrer = env.next_user();
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => Some(Key::Account(referrer)),
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let referral_tokens: U256 = liquidity_transformer
+        .query_dictionary("referral_tokens", key_to_str(&Key::Account(referrer)))
+        .unwrap_or_default();
+    assert!(referral_tokens > 0.into(), "Referrer not credited");
+    // The referrer stays out of the investor registry
+    let investor_count: U256 = liquidity_transformer
+        .query_dictionary("globals", "investor_count".into())
+        .unwrap_or_default();
+    assert_eq!(investor_count, 1.into(), "Referrer counted as investor");
+    let referrer_count: U256 = liquidity_transformer
+        .query_dictionary("globals", "referrer_count".into())
+        .unwrap_or_default();
+    assert_eq!(referrer_count, 1.into(), "Referrer not indexed");
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investors_batch",
+            "start" => None::<U256>,
+            "count" => U256::from(2)
+        },
+        time,
+    );
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(referrer)))
+        .unwrap_or_default();
+    assert_eq!(balance, referral_tokens, "Referrer not paid by the batch");
+    let ret: U256 = liquidity_transformer
+        .query_dictionary("referral_tokens", key_to_str(&Key::Account(referrer)))
+        .unwrap_or_default();
+    assert_eq!(ret, 0.into(), "Referral tokens left after payout");
+}
+
+#[test]
+fn test_payout_batch_skips_locked_investor_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investment_mode",
+        runtime_args! {
+            "investment_mode" => 2_u8,
+            "cash_back_rate" => U256::from(0),
+            "token_bonus" => U256::from(0),
+            "lock_days" => 5_u64
+        },
+        now(),
+    );
+    let users = [owner, env.next_user(), env.next_user()];
+    for (user, investment_mode) in users.iter().copied().zip([1_u8, 2_u8, 1_u8]) {
+        session_code_call(
+            &env,
+            user,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => investment_mode,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        );
+    }
+
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investors_batch",
+            "start" => None::<U256>,
+            "count" => U256::from(3)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "payout_investors_batch");
+    assert_eq!(
+        ret,
+        5280004000000000u64.into(),
+        "Unlocked investors not paid"
+    );
+    // The cursor moves past the locked investor so later batches reach new slots
+    let cursor: U256 = liquidity_transformer
+        .query_dictionary("globals", "payout_cursor".into())
+        .unwrap_or_default();
+    assert_eq!(cursor, 3.into(), "Cursor pinned by the locked investor");
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(users[1])))
+        .unwrap_or_default();
+    assert_eq!(balance, 0.into(), "Locked investor paid");
+
+    // The locked investor claims once the lock is over
+    liquidity_transformer.call_contract(
+        users[1],
+        "get_my_tokens",
+        runtime_args! {},
+        time + 10 * MILLI_SECONDS_IN_DAY,
+    );
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(users[1])))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        2640002000000000u64.into(),
+        "Locked investor not paid after unlock"
+    );
+}
+
+#[test]
+fn test_upgrade_migrates_legacy_registry_flow() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy_legacy();
+
+    let user = env.next_user();
+    let reserve = |investor| {
+        session_code_call(
+            &env,
+            investor,
+            runtime_args! {
+                "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+                "entrypoint" => "reserve_wise",
+                "investment_mode" => 1_u8,
+                "allowlist_proof" => None::<(U256, Vec<String>)>,
+                "kyc_approval" => None::<((U256, u64, U256), String)>,
+                "beneficiary" => None::<Key>,
+                "referral_address" => None::<Key>,
+                "amount" => TWOTHOUSEND_CSPR
+            },
+            now(),
+        )
+    };
+    // The legacy version appends to unique_investors and bumps investor_count on the
+    // first reservation of an investor only
+    reserve(user);
+    reserve(owner);
+    reserve(owner);
+    let slots: U256 = liquidity_transformer
+        .query_dictionary("globals", "investor_count".into())
+        .unwrap_or_default();
+    assert_eq!(slots, 2.into(), "Legacy slots not written");
+    let first: Key = liquidity_transformer
+        .query_dictionary("unique_investors", "0".into())
+        .unwrap();
+    assert_eq!(first, Key::Account(user), "Legacy slot not written");
+    let balance: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+
+    let liquidity_transformer = upgrade_liquidity_transformer(&env, owner, time);
+    // The dictionaries of the legacy version are still reachable from the new one
+    let user_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(user_balance, balance, "Investor balance lost on upgrade");
+    let owner_balance: U256 = liquidity_transformer
+        .query_dictionary("investor_balance", key_to_str(&Key::Account(owner)))
+        .unwrap_or_default();
+    assert_eq!(
+        owner_balance,
+        balance * 2,
+        "Investor balance lost on upgrade"
+    );
+    let total_cspr_contributed: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_cspr_contributed".into())
+        .unwrap_or_default();
+    assert_eq!(
+        total_cspr_contributed,
+        balance * 3,
+        "Globals lost on upgrade"
+    );
+    let migrated: bool = liquidity_transformer
+        .query_dictionary("globals", "registry_migrated".into())
+        .unwrap_or_default();
+    assert!(!migrated, "Legacy registry marked as migrated");
+
+    liquidity_transformer.call_contract(
+        owner,
+        "migrate_investor_registry",
+        runtime_args! {
+            "count" => U256::from(10)
+        },
+        time,
+    );
+    let migrated: bool = liquidity_transformer
+        .query_dictionary("globals", "registry_migrated".into())
+        .unwrap_or_default();
+    assert!(migrated, "Registry not migrated");
+    let unique_investors: U256 = liquidity_transformer
+        .query_dictionary("globals", "unique_investor_count".into())
+        .unwrap_or_default();
+    assert_eq!(unique_investors, 2.into(), "Legacy investors not counted");
+    let index: U256 = liquidity_transformer
+        .query_dictionary("investor_index", key_to_str(&Key::Account(user)))
+        .unwrap_or_default();
+    assert_eq!(index, 1.into(), "Investor not indexed at the first slot");
+
+    // Reservations resume on the migrated registry without adding slots
+    reserve(user);
+    let slots: U256 = liquidity_transformer
+        .query_dictionary("globals", "investor_count".into())
+        .unwrap_or_default();
+    assert_eq!(slots, 2.into(), "Known investor appended again");
+}
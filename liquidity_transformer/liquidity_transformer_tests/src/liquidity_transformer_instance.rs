@@ -1,8 +1,9 @@
 use std::time::SystemTime;
 
 use casper_types::{
-    account::AccountHash, bytesrepr::FromBytes, runtime_args, CLTyped, Key, RuntimeArgs, URef,
-    U256, U512,
+    account::AccountHash,
+    bytesrepr::{FromBytes, ToBytes},
+    crypto, runtime_args, CLTyped, Key, PublicKey, RuntimeArgs, SecretKey, URef, U256, U512,
 };
 use casperlabs_test_env::{TestContract, TestEnv};
 
@@ -38,6 +39,13 @@ impl LIQUIDITYTRANSFORMERInstance {
                 "pair_scspr" => pair_scspr,
                 "uniswap_router" => uniswap_router,
                 "wcspr" => wcspr,
+                "investment_days" => INVESTMENT_DAYS,
+                "max_supply" => MAX_SUPPLY,
+                "max_invest" => MAX_INVEST,
+                "token_cost" => TOKEN_COST,
+                "refund_cap" => REFUND_CAP,
+                "vesting_cliff_days" => 0_u64,
+                "vesting_days" => 0_u64,
                 "amount" => amount
             },
             time,
@@ -64,6 +72,7 @@ impl LIQUIDITYTRANSFORMERInstance {
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn reserve_wise_with_token(
         &self,
         sender: AccountHash,
@@ -71,6 +80,10 @@ impl LIQUIDITYTRANSFORMERInstance {
         token_address: Key,
         token_amount: U256,
         investment_mode: u8,
+        min_cspr_out: U256,
+        deadline: U256,
+        path: Option<Vec<Key>>,
+        referral_address: Option<Key>,
         time: u64,
     ) {
         self.0.call_contract(
@@ -80,7 +93,11 @@ impl LIQUIDITYTRANSFORMERInstance {
                 "proxy" => proxy,
                 "token_address" => token_address,
                 "token_amount" => token_amount,
-                "investment_mode" => investment_mode
+                "investment_mode" => investment_mode,
+                "min_cspr_out" => min_cspr_out,
+                "deadline" => deadline,
+                "path" => path,
+                "referral_address" => referral_address
             },
             time,
         );
@@ -190,6 +207,12 @@ pub const TRANSFORMER_AMOUNT: U512 = U512([50_000_000_000, 0, 0, 0, 0, 0, 0, 0])
 pub const STAKEABLE_AMOUNT: U512 = U512([0, 0, 0, 0, 0, 0, 0, 0]);
 pub const TWOTHOUSEND_CSPR: U512 = U512([2_000_000_000_000, 0, 0, 0, 0, 0, 0, 0]);
 
+pub const INVESTMENT_DAYS: u8 = 15;
+pub const MAX_SUPPLY: U256 = U256([264_000_000_000_000_000, 0, 0, 0]); // 264000000E9
+pub const MAX_INVEST: U256 = U256([200_000_000_000_000, 0, 0, 0]); // 200000E9
+pub const TOKEN_COST: U256 = U256([757_575, 0, 0, 0]); // MAX_INVEST / (MAX_SUPPLY / 1E9)
+pub const REFUND_CAP: U256 = U256([100_000_000_000, 0, 0, 0]); // 100E9
+
 pub fn now() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -197,6 +220,50 @@ pub fn now() -> u64 {
         .as_millis() as u64
 }
 
+// Builds the message the same way the contract does in kyc_digest: keccak256 over
+// "{package}_{caller}_{investor}_{max_amount}_{expiry}_{nonce}", hex encoded
+pub fn kyc_digest(
+    package: Key,
+    caller: Key,
+    investor: Key,
+    max_amount: U256,
+    expiry: u64,
+    nonce: U256,
+) -> String {
+    let message: String = format!(
+        "{}_{}_{}_{}_{}_{}",
+        package.to_formatted_string(),
+        caller.to_formatted_string(),
+        investor.to_formatted_string(),
+        max_amount,
+        expiry,
+        nonce
+    );
+    hex::encode(renvm_sig::keccak256(message.as_bytes()))
+}
+
+// Signs the UTF-8 bytes of the hex digest, crypto::sign hashes them again with SHA-256
+#[allow(clippy::too_many_arguments)]
+pub fn sign_kyc_approval(
+    secret_key: &SecretKey,
+    package: Key,
+    caller: Key,
+    investor: Key,
+    max_amount: U256,
+    expiry: u64,
+    nonce: U256,
+) -> ((U256, u64, U256), String) {
+    let digest: String = kyc_digest(package, caller, investor, max_amount, expiry, nonce);
+    let public_key: PublicKey = PublicKey::from(secret_key);
+    let signature = crypto::sign(digest.as_bytes(), secret_key, &public_key);
+    // Drop the tag byte, the contract expects the 64 bytes compact signature
+    let signature_bytes: Vec<u8> = signature.to_bytes().unwrap();
+    (
+        (max_amount, expiry, nonce),
+        hex::encode(&signature_bytes[1..]),
+    )
+}
+
 pub fn key_to_str(key: &Key) -> String {
     match key {
         Key::Account(account) => account.to_string(),
@@ -419,6 +486,7 @@ pub fn deploy_scspr(
 #[allow(clippy::new_ret_no_self, clippy::too_many_arguments)]
 pub fn deploy_liquidity_transformer(
     env: &TestEnv,
+    wasm: &str,
     contract_name: &str,
     sender: AccountHash,
     stakeable: Key,
@@ -427,12 +495,14 @@ pub fn deploy_liquidity_transformer(
     pair_scspr: Key,
     uniswap_router: Key,
     wcspr: Key,
+    vesting_cliff_days: u64,
+    vesting_days: u64,
     amount: U512,
     time: u64,
 ) -> TestContract {
     TestContract::new(
         env,
-        "liquidity_transformer.wasm",
+        wasm,
         contract_name,
         sender,
         runtime_args! {
@@ -442,12 +512,77 @@ pub fn deploy_liquidity_transformer(
             "pair_scspr" => pair_scspr,
             "uniswap_router" => uniswap_router,
             "wcspr" => wcspr,
+            "investment_days" => INVESTMENT_DAYS,
+            "max_supply" => MAX_SUPPLY,
+            "max_invest" => MAX_INVEST,
+            "token_cost" => TOKEN_COST,
+            "refund_cap" => REFUND_CAP,
+            "vesting_cliff_days" => vesting_cliff_days,
+            "vesting_days" => vesting_days,
             "amount" => amount
         },
         time,
     )
 }
 
+// Deploys a transformer with placeholder addresses to check the sale parameter validation
+pub fn deploy_with_sale_parameters(
+    max_supply: U256,
+    max_invest: U256,
+    token_cost: U256,
+) -> (TestContract, AccountHash) {
+    let env = TestEnv::new();
+    let owner = env.next_user();
+    let liquidity_transformer = TestContract::new(
+        &env,
+        "liquidity_transformer.wasm",
+        "LIQUIDITY_TRANSFORMER",
+        owner,
+        runtime_args! {
+            "wise" => Key::Account(owner),
+            "scspr" => Key::Account(owner),
+            "pair_wise" => Key::Account(owner),
+            "pair_scspr" => Key::Account(owner),
+            "uniswap_router" => Key::Account(owner),
+            "wcspr" => Key::Account(owner),
+            "investment_days" => INVESTMENT_DAYS,
+            "max_supply" => max_supply,
+            "max_invest" => max_invest,
+            "token_cost" => token_cost,
+            "refund_cap" => REFUND_CAP,
+            "vesting_cliff_days" => 0_u64,
+            "vesting_days" => 0_u64,
+            "amount" => U512::from(0)
+        },
+        now(),
+    );
+    (liquidity_transformer, owner)
+}
+
+pub fn deploy_lp_locker(
+    env: &TestEnv,
+    owner: AccountHash,
+    lp_token: Key,
+    beneficiary: Key,
+    stakeable: Key,
+    unlock_day: u64,
+    time: u64,
+) -> TestContract {
+    TestContract::new(
+        env,
+        "lp_locker.wasm",
+        "lp_locker",
+        owner,
+        runtime_args! {
+            "lp_token" => lp_token,
+            "beneficiary" => beneficiary,
+            "stakeable" => stakeable,
+            "unlock_day" => unlock_day
+        },
+        time,
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn deploy_stakeable(
     env: &TestEnv,
@@ -481,6 +616,17 @@ pub fn deploy_stakeable(
     )
 }
 
+pub fn deploy_stakeable_double(env: &TestEnv, owner: AccountHash, time: u64) -> TestContract {
+    TestContract::new(
+        env,
+        "stakeable-token-double.wasm",
+        "stakeable-token-double",
+        owner,
+        runtime_args! {},
+        time,
+    )
+}
+
 #[allow(clippy::type_complexity)]
 pub fn deploy() -> (
     TestEnv,
@@ -497,6 +643,101 @@ pub fn deploy() -> (
     TestContract,
     TestContract,
     u64,
+) {
+    deploy_with_vesting(0, 0)
+}
+
+// Deploys the transformer release that predates the investor registry
+#[allow(clippy::type_complexity)]
+pub fn deploy_legacy() -> (
+    TestEnv,
+    TestContract,
+    AccountHash,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    u64,
+) {
+    deploy_transformer_wasm("liquidity_transformer_legacy.wasm", 0, 0, false)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn deploy_with_vesting(
+    vesting_cliff_days: u64,
+    vesting_days: u64,
+) -> (
+    TestEnv,
+    TestContract,
+    AccountHash,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    u64,
+) {
+    deploy_transformer_wasm(
+        "liquidity_transformer.wasm",
+        vesting_cliff_days,
+        vesting_days,
+        false,
+    )
+}
+
+// Same as `deploy` with `stakeable-token-double.wasm` standing in for the WISE contract
+#[allow(clippy::type_complexity)]
+pub fn deploy_with_stakeable_double() -> (
+    TestEnv,
+    TestContract,
+    AccountHash,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    u64,
+) {
+    deploy_transformer_wasm("liquidity_transformer.wasm", 0, 0, true)
+}
+
+#[allow(clippy::type_complexity)]
+fn deploy_transformer_wasm(
+    wasm: &str,
+    vesting_cliff_days: u64,
+    vesting_days: u64,
+    stakeable_double: bool,
+) -> (
+    TestEnv,
+    TestContract,
+    AccountHash,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    TestContract,
+    u64,
 ) {
     let time = now();
     let env = TestEnv::new();
@@ -557,21 +798,26 @@ pub fn deploy() -> (
         SCSPR_AMOUNT,
         time,
     );
-    let stakeable_token = deploy_stakeable(
-        &env,
-        owner,
-        &erc20,
-        &scspr,
-        &wcspr,
-        &uniswap_router,
-        &uniswap_factory,
-        &pair_stakeable,
-        &liquidity_guard,
-        STAKEABLE_AMOUNT,
-        time - (2 * MILLI_SECONDS_IN_DAY), // 172800000 == 2 days in ms (launch time set in past for testing)
-    );
+    let stakeable_token = if stakeable_double {
+        deploy_stakeable_double(&env, owner, time - (2 * MILLI_SECONDS_IN_DAY))
+    } else {
+        deploy_stakeable(
+            &env,
+            owner,
+            &erc20,
+            &scspr,
+            &wcspr,
+            &uniswap_router,
+            &uniswap_factory,
+            &pair_stakeable,
+            &liquidity_guard,
+            STAKEABLE_AMOUNT,
+            time - (2 * MILLI_SECONDS_IN_DAY), // 172800000 == 2 days in ms (launch time set in past for testing)
+        )
+    };
     let liquidity_transformer = deploy_liquidity_transformer(
         &env,
+        wasm,
         "LIQUIDITY_TRANSFORMER",
         owner,
         Key::Hash(stakeable_token.package_hash()),
@@ -580,6 +826,8 @@ pub fn deploy() -> (
         Key::Hash(pair_scspr.package_hash()),
         Key::Hash(uniswap_router.package_hash()),
         Key::Hash(wcspr.package_hash()),
+        vesting_cliff_days,
+        vesting_days,
         TRANSFORMER_AMOUNT,
         time,
     );
@@ -638,6 +886,26 @@ pub fn deploy() -> (
     )
 }
 
+// Installs the current wasm as a new version of the package deployed by `deploy_legacy`
+pub fn upgrade_liquidity_transformer(env: &TestEnv, owner: AccountHash, time: u64) -> TestContract {
+    TestContract::new(
+        env,
+        "liquidity_transformer.wasm",
+        "LIQUIDITY_TRANSFORMER",
+        owner,
+        runtime_args! {
+            "investment_days" => INVESTMENT_DAYS,
+            "max_supply" => MAX_SUPPLY,
+            "max_invest" => MAX_INVEST,
+            "token_cost" => TOKEN_COST,
+            "refund_cap" => REFUND_CAP,
+            "vesting_cliff_days" => 0_u64,
+            "vesting_days" => 0_u64
+        },
+        time,
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn forward_liquidity(
     env: &TestEnv,
@@ -646,6 +914,21 @@ pub fn forward_liquidity(
     token: &TestContract,
     scspr: &TestContract,
     time: u64,
+) -> u64 {
+    // Forward liquidity to be done after investment days
+    const INVESTMENT_DAY: u64 = 20 * MILLI_SECONDS_IN_DAY;
+    forward_liquidity_after(env, lt, owner, token, scspr, time, INVESTMENT_DAY)
+}
+
+// Registers the transformer with the stakeable and SCSPR, then forwards `delay` ms after `time`
+pub fn forward_liquidity_after(
+    env: &TestEnv,
+    lt: &TestContract,
+    owner: AccountHash,
+    token: &TestContract,
+    scspr: &TestContract,
+    time: u64,
+    delay: u64,
 ) -> u64 {
     scspr.call_contract(
         owner,
@@ -668,15 +951,44 @@ pub fn forward_liquidity(
         },
         time,
     );
-    // Forward liquidity to be done after investment days
-    const INVESTMENT_DAY: u64 = 20 * MILLI_SECONDS_IN_DAY;
-    lt.call_contract(
+    lt.call_contract(owner, "forward_liquidity", runtime_args! {}, time + delay);
+    time + delay
+}
+
+// Sells the whole supply on the first stakeable day and forwards the liquidity on the next one,
+// returns the investor and the time of the forward
+pub fn sell_out_and_forward() -> (TestEnv, TestContract, AccountHash, AccountHash, u64) {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => U512::from(MAX_INVEST.as_u128())
+        },
+        time,
+    );
+    let close_day: u64 = liquidity_transformer
+        .query_dictionary("globals", "close_day".into())
+        .unwrap_or_default();
+    assert!(close_day > 0, "Sale not closed at hard cap");
+    let time = forward_liquidity_after(
+        &env,
+        &liquidity_transformer,
         owner,
-        "forward_liquidity",
-        runtime_args! {},
-        time + INVESTMENT_DAY,
+        &wise,
+        &scspr,
+        time,
+        MILLI_SECONDS_IN_DAY,
     );
-    time + INVESTMENT_DAY
+    (env, liquidity_transformer, owner, user, time)
 }
 
 pub fn add_liquidity(
@@ -746,3 +1058,82 @@ pub fn add_liquidity(
         time,
     );
 }
+
+// Adds WISE/SCSPR liquidity to pair_wise before the sale forwards it, minting WISE through the
+// stakeable double and SCSPR through liquidity_deposit with the owner standing in as transformer
+#[allow(clippy::too_many_arguments)]
+pub fn seed_pair_wise(
+    env: &TestEnv,
+    owner: AccountHash,
+    wise: &TestContract,
+    scspr: &TestContract,
+    uniswap_router: &TestContract,
+    pair_wise: &TestContract,
+    wise_amount: U256,
+    scspr_amount: U256,
+    time: u64,
+) {
+    wise.call_contract(
+        owner,
+        "set_transformer",
+        runtime_args! {
+            "transformer" => Key::Account(owner)
+        },
+        time,
+    );
+    scspr.call_contract(
+        owner,
+        "set_wise",
+        runtime_args! {
+            "wise" => Key::Hash(wise.package_hash())
+        },
+        time,
+    );
+    session_code_call(
+        env,
+        owner,
+        runtime_args! {
+            "entrypoint" => "liquidity_deposit",
+            "package_hash" => Key::Hash(scspr.package_hash()),
+            "amount" => U512::from(scspr_amount.as_u128()),
+        },
+        time,
+    );
+    wise.call_contract(
+        owner,
+        "mint",
+        runtime_args! {
+            "to" => Key::Account(owner),
+            "amount" => wise_amount
+        },
+        time,
+    );
+    for (token, amount) in [(wise, wise_amount), (scspr, scspr_amount)] {
+        token.call_contract(
+            owner,
+            "approve",
+            runtime_args! {
+                "spender" => Key::Hash(uniswap_router.package_hash()),
+                "amount" => amount
+            },
+            time,
+        );
+    }
+    let deadline = time + (30 * 60 * MILLI_SECONDS_IN_DAY);
+    uniswap_router.call_contract(
+        owner,
+        "add_liquidity",
+        runtime_args! {
+            "token_a" => Key::Hash(wise.package_hash()),
+            "token_b" => Key::Hash(scspr.package_hash()),
+            "amount_a_desired" => wise_amount,
+            "amount_b_desired" => scspr_amount,
+            "amount_a_min" => wise_amount,
+            "amount_b_min" => scspr_amount,
+            "to" => Key::Account(owner),
+            "pair" => Some(Key::Hash(pair_wise.package_hash())),
+            "deadline" => U256::from(deadline),
+        },
+        time,
+    );
+}
@@ -1,5 +1,8 @@
 use crate::liquidity_transformer_instance::*;
-use casper_types::{runtime_args, Key, RuntimeArgs, U256, U512};
+use casper_types::{
+    account::AccountHash, crypto, runtime_args, Key, PublicKey, RuntimeArgs, SecretKey, Signature,
+    U256, U512,
+};
 use num_traits::cast::AsPrimitive;
 
 #[test]
@@ -7,6 +10,51 @@ fn test_deploy() {
     let (_, _, _, _, _, _, _, _, _, _, _, _, _, _) = deploy();
 }
 
+#[test]
+#[should_panic]
+fn test_deploy_with_zero_max_supply() {
+    deploy_with_sale_parameters(0.into(), MAX_INVEST, TOKEN_COST);
+}
+
+#[test]
+#[should_panic]
+fn test_deploy_with_zero_max_invest() {
+    deploy_with_sale_parameters(MAX_SUPPLY, 0.into(), TOKEN_COST);
+}
+
+#[test]
+#[should_panic]
+fn test_fixed_price_with_inconsistent_max_invest() {
+    // Twice the invest cap for the same supply and token cost
+    let (liquidity_transformer, owner) =
+        deploy_with_sale_parameters(MAX_SUPPLY, MAX_INVEST * 2, TOKEN_COST);
+    liquidity_transformer.call_contract(
+        owner,
+        "set_sale_mode",
+        runtime_args! {
+            "sale_mode" => 0_u8
+        },
+        now(),
+    );
+}
+
+#[test]
+fn test_bonding_curve_with_independent_token_cost() {
+    // The curve starting price is not tied to MAX_INVEST / MAX_SUPPLY
+    let (liquidity_transformer, owner) =
+        deploy_with_sale_parameters(MAX_SUPPLY, MAX_INVEST * 2, TOKEN_COST);
+    liquidity_transformer.call_contract(
+        owner,
+        "set_sale_mode",
+        runtime_args! {
+            "sale_mode" => 2_u8
+        },
+        now(),
+    );
+    let sale_mode: u8 = liquidity_transformer.query_named_key("sale_mode".to_string());
+    assert_eq!(sale_mode, 2, "Sale mode not set");
+}
+
 #[test]
 fn test_current_stakeable_day() {
     let (env, lt, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
@@ -95,6 +143,99 @@ fn test_renounce_keeper() {
     assert_eq!(res, zero, "Keeper not renounced");
 }
 
+#[test]
+fn test_sale_parameters() {
+    let (_, liquidity_transformer, _, _, _, _, _, _, _, _, _, _, _, _) = deploy();
+    let investment_days: u8 = liquidity_transformer.query_named_key("investment_days".to_string());
+    let max_supply: U256 = liquidity_transformer.query_named_key("max_supply".to_string());
+    let max_invest: U256 = liquidity_transformer.query_named_key("max_invest".to_string());
+    let token_cost: U256 = liquidity_transformer.query_named_key("token_cost".to_string());
+    let refund_cap: U256 = liquidity_transformer.query_named_key("refund_cap".to_string());
+    assert_eq!(investment_days, INVESTMENT_DAYS, "Investment days not set");
+    assert_eq!(max_supply, MAX_SUPPLY, "Max supply not set");
+    assert_eq!(max_invest, MAX_INVEST, "Max invest not set");
+    assert_eq!(token_cost, TOKEN_COST, "Token cost not set");
+    assert_eq!(refund_cap, REFUND_CAP, "Refund cap not set");
+}
+
+#[test]
+fn test_set_price_tolerance() {
+    let (_, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let price_tolerance: U256 =
+        liquidity_transformer.query_named_key("price_tolerance".to_string());
+    assert_eq!(price_tolerance, 1.into(), "Invalid default price tolerance");
+    liquidity_transformer.call_contract(
+        owner,
+        "set_price_tolerance",
+        runtime_args! {
+            "price_tolerance" => U256::from(5)
+        },
+        time,
+    );
+    let price_tolerance: U256 =
+        liquidity_transformer.query_named_key("price_tolerance".to_string());
+    assert_eq!(price_tolerance, 5.into(), "Price tolerance not set");
+}
+
+#[test]
+#[should_panic]
+fn test_set_sale_mode_after_sale_started() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_sale_mode",
+        runtime_args! {
+            "sale_mode" => 1_u8
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_set_bonding_curve_too_many_steps() {
+    let (_, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_bonding_curve",
+        runtime_args! {
+            "curve_type" => 1_u8,
+            "factor" => U256::from(5),
+            "step" => MAX_SUPPLY / 1000
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_set_price_tolerance_above_hundred() {
+    let (_, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_price_tolerance",
+        runtime_args! {
+            "price_tolerance" => U256::from(101)
+        },
+        time,
+    );
+}
+
 #[test]
 fn test_reserve_wise() {
     let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
@@ -117,6 +258,10 @@ fn test_reserve_wise() {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => investment_mode,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => msg_value,
         },
         now() + TIME,
@@ -172,6 +317,17 @@ fn test_reserve_wise_with_token() {
     );
     const AMOUNT: u128 = 100_000_000;
     let investment_mode: u8 = 1;
+    liquidity_transformer.call_contract(
+        owner,
+        "set_accepted_token",
+        runtime_args! {
+            "token_address" => Key::Hash(erc20.package_hash()),
+            "accepted" => true,
+            "min_amount" => U256::from(AMOUNT),
+            "max_amount" => U256::from(AMOUNT)
+        },
+        now(),
+    );
     erc20.call_contract(
         owner,
         "approve",
@@ -200,6 +356,13 @@ fn test_reserve_wise_with_token() {
             "token_address" => Key::Hash(erc20.package_hash()),
             "token_amount" => U256::from(AMOUNT),
             "investment_mode" => investment_mode,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "min_cspr_out" => U256::from(98_000_000),
+            "deadline" => U256::from(now() + TIME + MILLI_SECONDS_IN_DAY),
+            "path" => None::<Vec<Key>>,
         },
         now() + TIME,
     );
@@ -211,70 +374,197 @@ fn test_reserve_wise_with_token() {
         98715803.into(), // Not exactly equal to AMOUNT due to fee cutting during 'swap_exact_tokens_for_cspr'
         "Investor wise balance not increased"
     );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "accepted_token",
+            "token_address" => Key::Hash(erc20.package_hash()),
+        },
+        now() + TIME,
+    );
+    let ret: ((bool, U256, U256), U256) = session_code_result(&env, owner, "accepted_token");
+    assert_eq!(
+        ret,
+        ((true, AMOUNT.into(), AMOUNT.into()), AMOUNT.into()),
+        "Accepted token total not tracked"
+    );
 }
 
 #[test]
-fn test_forward_liquidity() {
-    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
-    let uniswap_swaped: bool = liquidity_transformer
-        .query_dictionary("globals", "uniswap_swaped".into())
-        .unwrap_or_default();
-    assert!(
-        !uniswap_swaped,
-        "Reserved tokens equivalent to CSPR contributed already forwarded"
-    );
+#[should_panic]
+fn test_reserve_wise_with_token_not_accepted() {
+    let (env, liquidity_transformer, owner, erc20, _, _, _, _, _, _, _, _, _, _) = deploy();
+    const AMOUNT: u128 = 100_000_000;
+    const DAYS: u64 = 12;
+    const TIME: u64 = DAYS * 86400 * 1000;
     session_code_call(
         &env,
         owner,
         runtime_args! {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
-            "entrypoint" => "reserve_wise",
+            "entrypoint" => "reserve_wise_with_token",
+            "token_address" => Key::Hash(erc20.package_hash()),
+            "token_amount" => U256::from(AMOUNT),
             "investment_mode" => 1_u8,
-            "amount" => TWOTHOUSEND_CSPR
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "min_cspr_out" => U256::from(0),
+            "deadline" => U256::from(now() + TIME + MILLI_SECONDS_IN_DAY),
+            "path" => None::<Vec<Key>>,
         },
-        now(),
-    );
-    forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
-    let uniswap_swaped: bool = liquidity_transformer
-        .query_dictionary("globals", "uniswap_swaped".into())
-        .unwrap_or_default();
-    assert!(
-        uniswap_swaped,
-        "Reserved tokens equivalent to CSPR contributed not forwarded"
+        now() + TIME,
     );
 }
 
 #[test]
-fn test_payout_investor_address() {
-    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+#[should_panic]
+fn test_reserve_wise_with_token_after_deadline() {
+    let (env, liquidity_transformer, owner, erc20, _, _, _, _, _, _, _, _, _, _) = deploy();
+    const AMOUNT: u128 = 100_000_000;
+    const DAYS: u64 = 12;
+    const TIME: u64 = DAYS * 86400 * 1000;
     session_code_call(
         &env,
         owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise_with_token",
+            "token_address" => Key::Hash(erc20.package_hash()),
+            "token_amount" => U256::from(AMOUNT),
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "min_cspr_out" => U256::from(0),
+            "deadline" => U256::from(now()),
+            "path" => None::<Vec<Key>>,
+        },
+        now() + TIME,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_forward_liquidity_pre_seeded_pool_out_of_tolerance() {
+    let (env, liquidity_transformer, owner, _, _, router, _, wise, scspr, _, pair_wise, _, _, time) =
+        deploy_with_stakeable_double();
+
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
         runtime_args! {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => TWOTHOUSEND_CSPR
         },
         now(),
     );
-    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
-    session_code_call(
+    let wise_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_transfer_tokens".into())
+        .unwrap_or_default();
+    let scspr_total: U256 = liquidity_transformer
+        .query_dictionary("globals", "total_cspr_contributed".into())
+        .unwrap_or_default();
+
+    // A tenth of our liquidity at twice the sale price
+    seed_pair_wise(
         &env,
         owner,
+        &wise,
+        &scspr,
+        &router,
+        &pair_wise,
+        wise_total / 10,
+        scspr_total / 5,
+        time,
+    );
+
+    forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+}
+
+#[test]
+#[should_panic]
+fn test_set_lp_destination_invalid() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let treasury = env.next_user();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_lp_destination",
         runtime_args! {
-            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
-            "entrypoint" => "payout_investor_address",
-            "investor_address" => Key::Account(owner)
+            "lp_destination" => 5_u8,
+            "lp_recipient" => Key::Account(treasury)
         },
         time,
     );
-    let ret: U256 = session_code_result(&env, owner, "payout_investor_address");
-    assert_eq!(ret, 2640002000000000u64.into()); // calculated amount in contract
 }
 
 #[test]
-fn test_get_my_tokens() {
+#[should_panic]
+fn test_set_lp_destination_locker_wrong_lp_token() {
+    let (env, liquidity_transformer, owner, _, _, _, pair_scspr, wise, _, _, _, _, _, time) =
+        deploy();
+    let treasury = env.next_user();
+    let lp_locker = deploy_lp_locker(
+        &env,
+        owner,
+        Key::Hash(pair_scspr.package_hash()),
+        Key::Account(treasury),
+        Key::Hash(wise.package_hash()),
+        30,
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_lp_destination",
+        runtime_args! {
+            "lp_destination" => 2_u8,
+            "lp_recipient" => Key::Hash(lp_locker.package_hash())
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_set_lp_destination_locker_already_unlocked() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, _, _, pair_wise, _, _, time) =
+        deploy();
+    let treasury = env.next_user();
+    // Stakeable launched two days before the sale, so day 1 has already passed
+    let lp_locker = deploy_lp_locker(
+        &env,
+        owner,
+        Key::Hash(pair_wise.package_hash()),
+        Key::Account(treasury),
+        Key::Hash(wise.package_hash()),
+        1,
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_lp_destination",
+        runtime_args! {
+            "lp_destination" => 2_u8,
+            "lp_recipient" => Key::Hash(lp_locker.package_hash())
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_claim_lp_when_disabled() {
     let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
     session_code_call(
         &env,
@@ -283,49 +573,52 @@ fn test_get_my_tokens() {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => TWOTHOUSEND_CSPR
         },
         now(),
     );
     let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
-    let balance: U256 = wise
-        .query_dictionary("balances", key_to_str(&Key::Account(owner)))
-        .unwrap_or_default();
-    assert_eq!(balance, 0.into(), "Already have some wise tokens");
-    liquidity_transformer.call_contract(owner, "get_my_tokens", runtime_args! {}, time);
-    let balance: U256 = wise
-        .query_dictionary("balances", key_to_str(&Key::Account(owner)))
-        .unwrap_or_default();
-    assert_eq!(
-        balance,
-        2640002000000000u64.into(), // calculated amount in contract
-        "Tokens not transfered to owner"
-    );
+    liquidity_transformer.call_contract(owner, "claim_lp", runtime_args! {}, time);
 }
 
 #[test]
-fn test_prepare_path() {
-    let (env, liquidity_transformer, owner, erc20, wcspr, _, _, _, _, _, _, _, _, _) = deploy();
-    let token_address: Key = Key::Hash(erc20.package_hash());
+#[should_panic]
+fn test_forward_liquidity_below_soft_cap() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_soft_cap",
+        runtime_args! {
+            "soft_cap" => MAX_INVEST
+        },
+        time,
+    );
     session_code_call(
         &env,
         owner,
         runtime_args! {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
-            "entrypoint" => "prepare_path",
-            "token_address" => token_address,
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
         },
         now(),
     );
-    let ret: Vec<Key> = session_code_result(&env, owner, "prepare_path");
-    assert_eq!(ret[0], Key::Hash(erc20.package_hash()));
-    assert_eq!(ret[1], Key::Hash(wcspr.package_hash()));
+    forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
 }
 
 #[test]
-fn test_request_refund() {
-    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
-    // Using session code as caller of purse is required for reserving wise
+#[should_panic]
+fn test_forward_liquidity_before_close() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
     session_code_call(
         &env,
         owner,
@@ -333,29 +626,726 @@ fn test_request_refund() {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
             "entrypoint" => "reserve_wise",
             "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
             "amount" => TWOTHOUSEND_CSPR
         },
         now(),
     );
-    // TIME PASSED, NOW CAN REFUND
-    const DAYS: u64 = 30;
-    const TIME: u64 = DAYS * 86400 * 1000;
+    // Below the hard cap the sale stays open until the investment days end
+    let close_day: u64 = liquidity_transformer
+        .query_dictionary("globals", "close_day".into())
+        .unwrap_or_default();
+    assert_eq!(close_day, 0, "Sale closed below hard cap");
+    liquidity_transformer.call_contract(
+        owner,
+        "forward_liquidity",
+        runtime_args! {},
+        time + 2 * MILLI_SECONDS_IN_DAY,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_withdraw_reservation_below_investor_minimum() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let two_thousand: U256 =
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR);
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investor_limits",
+        runtime_args! {
+            "min_investment" => two_thousand / 2 + 1,
+            "max_investment" => U256::from(0),
+            "daily_cap" => U256::from(0)
+        },
+        time,
+    );
+    let user = env.next_user();
     session_code_call(
         &env,
-        owner,
+        user,
         runtime_args! {
             "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
-            "entrypoint" => "request_refund"
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
         },
-        now() + TIME,
+        time,
     );
-    let ret: (U256, U256) = session_code_result(&env, owner, "request_refund");
-    assert_eq!(
-        ret,
-        (
-            <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
-            2640002000000000u64.into() // calculated amount in contract
-        ),
-        "Invalid refund"
+    // Half of the reservation would leave the investor just below the minimum
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => two_thousand / 2
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_withdraw_reservation_after_close_day() {
+    let (env, liquidity_transformer, _, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => U512::from(MAX_INVEST.as_u128())
+        },
+        time,
+    );
+    // Sold out today, so the sale can be forwarded tomorrow and stays closed
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR)
+        },
+        time + MILLI_SECONDS_IN_DAY,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_wise_after_early_close_swap() {
+    let (env, liquidity_transformer, _, _, time) = sell_out_and_forward();
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_withdraw_reservation_after_early_close_swap() {
+    let (env, liquidity_transformer, _, user, time) = sell_out_and_forward();
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR)
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_wise_after_cancel_sale() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(owner, "cancel_sale", runtime_args! {}, time);
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_mark_sale_failed_during_investment_days() {
+    let (_, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_soft_cap",
+        runtime_args! {
+            "soft_cap" => U256::from(1)
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(owner, "mark_sale_failed", runtime_args! {}, now());
+}
+
+#[test]
+#[should_panic]
+fn test_withdraw_reservation_above_balance() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "withdraw_reservation",
+            "amount" => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR) + 1
+        },
+        now(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_migrate_investor_registry_already_migrated() {
+    let (_, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(
+        owner,
+        "migrate_investor_registry",
+        runtime_args! {
+            "count" => U256::from(10)
+        },
+        time,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_wise_above_investor_maximum() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_investor_limits",
+        runtime_args! {
+            "min_investment" => U256::from(0),
+            "max_investment" => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR) - 1,
+            "daily_cap" => U256::from(0)
+        },
+        time,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_wise_without_kyc_approval() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let kyc_secret = SecretKey::secp256k1_from_bytes([7u8; 32]).unwrap();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_kyc_signer",
+        runtime_args! {
+            "kyc_signer" => PublicKey::from(&kyc_secret)
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_compliance_mode",
+        runtime_args! {
+            "compliance_mode" => true
+        },
+        time,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_wise_with_kyc_approval_of_beneficiary() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    let kyc_secret = SecretKey::secp256k1_from_bytes([7u8; 32]).unwrap();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_kyc_signer",
+        runtime_args! {
+            "kyc_signer" => PublicKey::from(&kyc_secret)
+        },
+        time,
+    );
+    liquidity_transformer.call_contract(
+        owner,
+        "set_compliance_mode",
+        runtime_args! {
+            "compliance_mode" => true
+        },
+        time,
+    );
+    // The owner replays the approval the user got for itself by reserving on their behalf
+    let user = env.next_user();
+    let kyc_approval = sign_kyc_approval(
+        &kyc_secret,
+        Key::Hash(liquidity_transformer.package_hash()),
+        Key::Account(user),
+        Key::Account(user),
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        now() + MILLI_SECONDS_IN_DAY,
+        1.into(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => Some(kyc_approval),
+            "beneficiary" => Some(Key::Account(user)),
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+}
+
+#[test]
+fn test_kyc_digest_vector() {
+    // Host-side signers can check their implementation against these values
+    let package = Key::Hash([1u8; 32]);
+    let caller = Key::Account(AccountHash::new([2u8; 32]));
+    let investor = Key::Account(AccountHash::new([3u8; 32]));
+    let digest: String = kyc_digest(
+        package,
+        caller,
+        investor,
+        1000.into(),
+        1700000000000,
+        1.into(),
+    );
+    assert_eq!(
+        digest, "0b070707d962fc3d48da8785546aa3d7e67b84992bc538868ef69ee69240acb4",
+        "Invalid KYC digest"
+    );
+
+    // The signature covers the hex string, not the raw keccak256 bytes
+    let kyc_secret = SecretKey::secp256k1_from_bytes([7u8; 32]).unwrap();
+    let kyc_signer = PublicKey::from(&kyc_secret);
+    let (_, signature) = sign_kyc_approval(
+        &kyc_secret,
+        package,
+        caller,
+        investor,
+        1000.into(),
+        1700000000000,
+        1.into(),
+    );
+    let mut signature_bytes: [u8; 64] = [0; 64];
+    hex::decode_to_slice(signature, &mut signature_bytes).unwrap();
+    let signature = Signature::secp256k1_from_bytes(signature_bytes).unwrap();
+    assert!(
+        crypto::verify(digest.as_bytes(), &signature, &kyc_signer).is_ok(),
+        "Signature not made over the hex digest"
+    );
+    assert!(
+        crypto::verify(hex::decode(&digest).unwrap(), &signature, &kyc_signer).is_err(),
+        "Signature made over the raw digest"
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reserve_wise_above_allowlist_cap() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, time) = deploy();
+    // The owner is allowlisted below the amount it tries to reserve
+    let csv: String = format!(
+        "{},{}\n{},{}\n",
+        Key::Account(owner).to_formatted_string(),
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR) - 1,
+        Key::Account(env.next_user()).to_formatted_string(),
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR)
+    );
+    let tree = liquidity_transformer_allowlist::MerkleTree::from_csv(csv.as_bytes()).unwrap();
+    liquidity_transformer.call_contract(
+        owner,
+        "set_allowlist_root",
+        runtime_args! {
+            "allowlist_root" => Some(tree.root_hex())
+        },
+        time,
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => tree.proof_hex(&Key::Account(owner)),
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+}
+
+#[test]
+fn test_forward_liquidity() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+    let uniswap_swaped: bool = liquidity_transformer
+        .query_dictionary("globals", "uniswap_swaped".into())
+        .unwrap_or_default();
+    assert!(
+        !uniswap_swaped,
+        "Reserved tokens equivalent to CSPR contributed already forwarded"
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+    let uniswap_swaped: bool = liquidity_transformer
+        .query_dictionary("globals", "uniswap_swaped".into())
+        .unwrap_or_default();
+    assert!(
+        uniswap_swaped,
+        "Reserved tokens equivalent to CSPR contributed not forwarded"
+    );
+}
+
+#[test]
+fn test_payout_investor_address() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "payout_investor_address",
+            "investor_address" => Key::Account(owner)
+        },
+        time,
+    );
+    let ret: U256 = session_code_result(&env, owner, "payout_investor_address");
+    assert_eq!(ret, 2640002000000000u64.into()); // calculated amount in contract
+}
+
+#[test]
+fn test_get_my_tokens() {
+    let (env, liquidity_transformer, owner, _, _, _, _, wise, scspr, _, _, _, _, time) = deploy();
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    let time = forward_liquidity(&env, &liquidity_transformer, owner, &wise, &scspr, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(owner)))
+        .unwrap_or_default();
+    assert_eq!(balance, 0.into(), "Already have some wise tokens");
+    liquidity_transformer.call_contract(owner, "get_my_tokens", runtime_args! {}, time);
+    let balance: U256 = wise
+        .query_dictionary("balances", key_to_str(&Key::Account(owner)))
+        .unwrap_or_default();
+    assert_eq!(
+        balance,
+        2640002000000000u64.into(), // calculated amount in contract
+        "Tokens not transfered to owner"
+    );
+}
+
+#[test]
+fn test_prepare_path() {
+    let (env, liquidity_transformer, owner, erc20, wcspr, _, _, _, _, _, _, _, _, _) = deploy();
+    let token_address: Key = Key::Hash(erc20.package_hash());
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "prepare_path",
+            "token_address" => token_address,
+        },
+        now(),
+    );
+    let ret: Vec<Key> = session_code_result(&env, owner, "prepare_path");
+    assert_eq!(ret[0], Key::Hash(erc20.package_hash()));
+    assert_eq!(ret[1], Key::Hash(wcspr.package_hash()));
+}
+
+#[test]
+fn test_prepare_path_with_swap_route() {
+    let (env, liquidity_transformer, owner, erc20, wcspr, _, _, _, scspr, _, _, _, _, _) = deploy();
+    let token_address: Key = Key::Hash(erc20.package_hash());
+    liquidity_transformer.call_contract(
+        owner,
+        "set_swap_route",
+        runtime_args! {
+            "token_address" => token_address,
+            "intermediate_tokens" => vec![Key::Hash(scspr.package_hash())]
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "prepare_path",
+            "token_address" => token_address,
+        },
+        now(),
+    );
+    let ret: Vec<Key> = session_code_result(&env, owner, "prepare_path");
+    assert_eq!(
+        ret,
+        vec![
+            token_address,
+            Key::Hash(scspr.package_hash()),
+            Key::Hash(wcspr.package_hash())
+        ],
+        "Invalid swap route"
+    );
+}
+
+#[test]
+fn test_request_refund() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
+    // Using session code as caller of purse is required for reserving wise
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    // TIME PASSED, NOW CAN REFUND
+    const DAYS: u64 = 30;
+    const TIME: u64 = DAYS * 86400 * 1000;
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "request_refund"
+        },
+        now() + TIME,
+    );
+    let ret: (U256, U256) = session_code_result(&env, owner, "request_refund");
+    assert_eq!(
+        ret,
+        (
+            <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+            2640002000000000u64.into() // calculated amount in contract
+        ),
+        "Invalid refund"
+    );
+}
+
+#[test]
+fn test_investor_and_sale_queries() {
+    let (env, liquidity_transformer, owner, _, _, _, _, _, _, _, _, _, _, _) = deploy();
+    let user = env.next_user();
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        user,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "reserve_wise",
+            "investment_mode" => 1_u8,
+            "allowlist_proof" => None::<(U256, Vec<String>)>,
+            "kyc_approval" => None::<((U256, u64, U256), String)>,
+            "beneficiary" => None::<Key>,
+            "referral_address" => None::<Key>,
+            "amount" => TWOTHOUSEND_CSPR
+        },
+        now(),
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "investor_balance",
+            "investor_address" => Key::Account(user)
+        },
+        now(),
+    );
+    let ret: U256 = session_code_result(&env, owner, "investor_balance");
+    assert_eq!(
+        ret,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR),
+        "Invalid investor balance"
+    );
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "purchased_tokens",
+            "investor_address" => Key::Account(user)
+        },
+        now(),
+    );
+    let ret: U256 = session_code_result(&env, owner, "purchased_tokens");
+    assert_eq!(ret, 2640002000000000u64.into(), "Invalid purchased tokens"); // calculated amount in contract
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "globals"
+        },
+        now(),
+    );
+    let ((_, investor_count), (total_transfer_tokens, total_cspr_contributed), uniswap_swaped): (
+        (U256, U256),
+        (U256, U256),
+        bool,
+    ) = session_code_result(&env, owner, "globals");
+    assert_eq!(investor_count, 2.into(), "Invalid investor count");
+    assert_eq!(
+        total_transfer_tokens,
+        5280004000000000u64.into(),
+        "Invalid total transfer tokens"
+    );
+    assert_eq!(
+        total_cspr_contributed,
+        <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(TWOTHOUSEND_CSPR * 2),
+        "Invalid total cspr contributed"
+    );
+    assert!(!uniswap_swaped, "Liquidity already forwarded");
+    session_code_call(
+        &env,
+        owner,
+        runtime_args! {
+            "package_hash" => Key::Hash(liquidity_transformer.package_hash()),
+            "entrypoint" => "unique_investors",
+            "offset" => U256::from(1),
+            "limit" => U256::from(10)
+        },
+        now(),
+    );
+    let ret: Vec<Key> = session_code_result(&env, owner, "unique_investors");
+    assert_eq!(
+        ret,
+        vec![Key::Account(user)],
+        "Invalid unique investors page"
     );
 }
@@ -23,18 +23,51 @@ pub const CALLER_PURSE_RUNTIME_ARG: &str = "caller_purse";
 pub const INVESTMENT_MODE_RUNTIME_ARG: &str = "investment_mode";
 pub const TOKEN_ADDRESS_RUNTIME_ARG: &str = "token_address";
 pub const TOKEN_AMOUNT_RUNTIME_ARG: &str = "token_amount";
+pub const MIN_CSPR_OUT_RUNTIME_ARG: &str = "min_cspr_out";
+pub const DEADLINE_RUNTIME_ARG: &str = "deadline";
+pub const PATH_RUNTIME_ARG: &str = "path";
+pub const BENEFICIARY_RUNTIME_ARG: &str = "beneficiary";
+pub const KYC_APPROVAL_RUNTIME_ARG: &str = "kyc_approval";
+pub const ALLOWLIST_PROOF_RUNTIME_ARG: &str = "allowlist_proof";
+pub const REFERRAL_ADDRESS_RUNTIME_ARG: &str = "referral_address";
 pub const INVESTOR_ADDRESS_RUNTIME_ARG: &str = "investor_address";
+pub const OFFSET_RUNTIME_ARG: &str = "offset";
+pub const LIMIT_RUNTIME_ARG: &str = "limit";
+pub const START_RUNTIME_ARG: &str = "start";
+pub const COUNT_RUNTIME_ARG: &str = "count";
+pub const DAY_RUNTIME_ARG: &str = "day";
+pub const TOTALS_RUNTIME_ARG: &str = "totals";
 
 pub const DEPOSIT: &str = "deposit";
+pub const LIQUIDITY_DEPOSIT: &str = "liquidity_deposit";
 pub const SET_LIQUIDITY_TRANSFOMER: &str = "set_liquidity_transfomer";
 pub const FORM_LIQUIDITY: &str = "form_liquidity";
 pub const FUND_CONTRACT: &str = "fund_contract";
 pub const RESERVE_WISE: &str = "reserve_wise";
 pub const RESERVE_WISE_WITH_TOKEN: &str = "reserve_wise_with_token";
 pub const REQUEST_REFUND: &str = "request_refund";
+pub const WITHDRAW_RESERVATION: &str = "withdraw_reservation";
+pub const SWEEP_WITHDRAW_PENALTIES: &str = "sweep_withdraw_penalties";
 pub const CURRENT_STAKEABLE_DAY: &str = "current_stakeable_day";
 pub const PAYOUT_INVESTOR_ADDRESS: &str = "payout_investor_address";
+pub const PAYOUT_INVESTORS_BATCH: &str = "payout_investors_batch";
 pub const PREPARE_PATH: &str = "prepare_path";
+pub const ACCEPTED_TOKEN: &str = "accepted_token";
+pub const REFERRAL_TOKENS: &str = "referral_tokens";
+pub const INVESTMENT_MODE_TOTALS: &str = "investment_mode_totals";
+pub const CLAIMABLE: &str = "claimable";
+pub const CLAIMABLE_LP: &str = "claimable_lp";
+pub const LP_TOTALS: &str = "lp_totals";
+pub const DAILY_TOTALS: &str = "daily_totals";
+pub const QUOTE_TOKENS: &str = "quote_tokens";
+pub const INVESTOR_DAILY_BALANCE: &str = "investor_daily_balance";
+pub const INVESTOR_BALANCE: &str = "investor_balance";
+pub const PURCHASED_TOKENS: &str = "purchased_tokens";
+pub const GLOBALS: &str = "globals";
+pub const CHECK_INVARIANTS: &str = "check_invariants";
+pub const UNIQUE_INVESTORS: &str = "unique_investors";
+pub const INVESTOR_COUNTS: &str = "investor_counts";
+pub const REMAINING_ALLOWANCE: &str = "remaining_allowance";
 
 #[repr(u32)]
 pub enum Error {
@@ -86,6 +119,19 @@ pub extern "C" fn call() {
             );
             store(DEPOSIT, ret);
         }
+        LIQUIDITY_DEPOSIT => {
+            let amount: U512 = runtime::get_named_arg(AMOUNT_RUNTIME_ARG);
+            let secondary_purse = temp_purse(amount);
+            runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                LIQUIDITY_DEPOSIT,
+                runtime_args! {
+                    AMOUNT_RUNTIME_ARG => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(amount),
+                    PURSE_RUNTIME_ARG => secondary_purse
+                },
+            )
+        }
         FUND_CONTRACT => {
             let amount: U512 = runtime::get_named_arg(AMOUNT_RUNTIME_ARG);
             let secondary_purse = temp_purse(amount);
@@ -136,6 +182,13 @@ pub extern "C" fn call() {
             let amount: U512 = runtime::get_named_arg(AMOUNT_RUNTIME_ARG);
             let secondary_purse = temp_purse(amount);
             let investment_mode: u8 = runtime::get_named_arg(INVESTMENT_MODE_RUNTIME_ARG);
+            let referral_address: Option<Key> =
+                runtime::get_named_arg(REFERRAL_ADDRESS_RUNTIME_ARG);
+            let beneficiary: Option<Key> = runtime::get_named_arg(BENEFICIARY_RUNTIME_ARG);
+            let kyc_approval: Option<((U256, u64, U256), String)> =
+                runtime::get_named_arg(KYC_APPROVAL_RUNTIME_ARG);
+            let allowlist_proof: Option<(U256, Vec<String>)> =
+                runtime::get_named_arg(ALLOWLIST_PROOF_RUNTIME_ARG);
             let () = runtime::call_versioned_contract(
                 package_hash.into_hash().unwrap_or_revert().into(),
                 None,
@@ -143,6 +196,10 @@ pub extern "C" fn call() {
                 runtime_args! {
                     INVESTMENT_MODE_RUNTIME_ARG => investment_mode,
                     MSG_VALUE_RUNTIME_ARG => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(amount),
+                    REFERRAL_ADDRESS_RUNTIME_ARG => referral_address,
+                    BENEFICIARY_RUNTIME_ARG => beneficiary,
+                    KYC_APPROVAL_RUNTIME_ARG => kyc_approval,
+                    ALLOWLIST_PROOF_RUNTIME_ARG => allowlist_proof,
                     CALLER_PURSE_RUNTIME_ARG => secondary_purse
                 },
             );
@@ -151,6 +208,16 @@ pub extern "C" fn call() {
             let token_address: Key = runtime::get_named_arg("token_address");
             let token_amount: U256 = runtime::get_named_arg("token_amount");
             let investment_mode: u8 = runtime::get_named_arg("investment_mode");
+            let min_cspr_out: U256 = runtime::get_named_arg(MIN_CSPR_OUT_RUNTIME_ARG);
+            let deadline: U256 = runtime::get_named_arg(DEADLINE_RUNTIME_ARG);
+            let path: Option<Vec<Key>> = runtime::get_named_arg(PATH_RUNTIME_ARG);
+            let referral_address: Option<Key> =
+                runtime::get_named_arg(REFERRAL_ADDRESS_RUNTIME_ARG);
+            let beneficiary: Option<Key> = runtime::get_named_arg(BENEFICIARY_RUNTIME_ARG);
+            let kyc_approval: Option<((U256, u64, U256), String)> =
+                runtime::get_named_arg(KYC_APPROVAL_RUNTIME_ARG);
+            let allowlist_proof: Option<(U256, Vec<String>)> =
+                runtime::get_named_arg(ALLOWLIST_PROOF_RUNTIME_ARG);
             let () = runtime::call_versioned_contract(
                 package_hash.into_hash().unwrap_or_revert().into(),
                 None,
@@ -159,6 +226,13 @@ pub extern "C" fn call() {
                     TOKEN_ADDRESS_RUNTIME_ARG => token_address,
                     TOKEN_AMOUNT_RUNTIME_ARG => token_amount,
                     INVESTMENT_MODE_RUNTIME_ARG => investment_mode,
+                    MIN_CSPR_OUT_RUNTIME_ARG => min_cspr_out,
+                    DEADLINE_RUNTIME_ARG => deadline,
+                    PATH_RUNTIME_ARG => path,
+                    REFERRAL_ADDRESS_RUNTIME_ARG => referral_address,
+                    BENEFICIARY_RUNTIME_ARG => beneficiary,
+                    KYC_APPROVAL_RUNTIME_ARG => kyc_approval,
+                    ALLOWLIST_PROOF_RUNTIME_ARG => allowlist_proof,
                     CALLER_PURSE_RUNTIME_ARG => account::get_main_purse()
                 },
             );
@@ -174,6 +248,30 @@ pub extern "C" fn call() {
             );
             store(REQUEST_REFUND, ret);
         }
+        WITHDRAW_RESERVATION => {
+            let amount: U256 = runtime::get_named_arg(AMOUNT_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                WITHDRAW_RESERVATION,
+                runtime_args! {
+                    AMOUNT_RUNTIME_ARG => amount,
+                    CALLER_PURSE_RUNTIME_ARG => account::get_main_purse()
+                },
+            );
+            store(WITHDRAW_RESERVATION, ret);
+        }
+        SWEEP_WITHDRAW_PENALTIES => {
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                SWEEP_WITHDRAW_PENALTIES,
+                runtime_args! {
+                    "treasury_purse" => account::get_main_purse()
+                },
+            );
+            store(SWEEP_WITHDRAW_PENALTIES, ret);
+        }
         CURRENT_STAKEABLE_DAY => {
             let ret: u64 = runtime::call_versioned_contract(
                 package_hash.into_hash().unwrap_or_revert().into(),
@@ -195,6 +293,20 @@ pub extern "C" fn call() {
             );
             store(PAYOUT_INVESTOR_ADDRESS, ret);
         }
+        PAYOUT_INVESTORS_BATCH => {
+            let start: Option<U256> = runtime::get_named_arg(START_RUNTIME_ARG);
+            let count: U256 = runtime::get_named_arg(COUNT_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                PAYOUT_INVESTORS_BATCH,
+                runtime_args! {
+                    START_RUNTIME_ARG => start,
+                    COUNT_RUNTIME_ARG => count
+                },
+            );
+            store(PAYOUT_INVESTORS_BATCH, ret);
+        }
         PREPARE_PATH => {
             let token_address: Key = runtime::get_named_arg(TOKEN_ADDRESS_RUNTIME_ARG);
             let ret: Vec<Key> = runtime::call_versioned_contract(
@@ -207,6 +319,200 @@ pub extern "C" fn call() {
             );
             store(PREPARE_PATH, ret);
         }
+        ACCEPTED_TOKEN => {
+            let token_address: Key = runtime::get_named_arg(TOKEN_ADDRESS_RUNTIME_ARG);
+            let ret: ((bool, U256, U256), U256) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                ACCEPTED_TOKEN,
+                runtime_args! {
+                    TOKEN_ADDRESS_RUNTIME_ARG => token_address
+                },
+            );
+            store(ACCEPTED_TOKEN, ret);
+        }
+        INVESTMENT_MODE_TOTALS => {
+            let investment_mode: u8 = runtime::get_named_arg(INVESTMENT_MODE_RUNTIME_ARG);
+            let ret: ((U256, U256), (U256, U256)) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                INVESTMENT_MODE_TOTALS,
+                runtime_args! {
+                    INVESTMENT_MODE_RUNTIME_ARG => investment_mode
+                },
+            );
+            store(INVESTMENT_MODE_TOTALS, ret);
+        }
+        REFERRAL_TOKENS => {
+            let referral_address: Key = runtime::get_named_arg(REFERRAL_ADDRESS_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                REFERRAL_TOKENS,
+                runtime_args! {
+                    REFERRAL_ADDRESS_RUNTIME_ARG => referral_address
+                },
+            );
+            store(REFERRAL_TOKENS, ret);
+        }
+        CLAIMABLE => {
+            let investor_address: Key = runtime::get_named_arg(INVESTOR_ADDRESS_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                CLAIMABLE,
+                runtime_args! {
+                    INVESTOR_ADDRESS_RUNTIME_ARG => investor_address
+                },
+            );
+            store(CLAIMABLE, ret);
+        }
+        CLAIMABLE_LP => {
+            let investor_address: Key = runtime::get_named_arg(INVESTOR_ADDRESS_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                CLAIMABLE_LP,
+                runtime_args! {
+                    INVESTOR_ADDRESS_RUNTIME_ARG => investor_address
+                },
+            );
+            store(CLAIMABLE_LP, ret);
+        }
+        LP_TOTALS => {
+            let ret: (U256, U256) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                LP_TOTALS,
+                runtime_args! {},
+            );
+            store(LP_TOTALS, ret);
+        }
+        INVESTOR_BALANCE => {
+            let investor_address: Key = runtime::get_named_arg(INVESTOR_ADDRESS_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                INVESTOR_BALANCE,
+                runtime_args! {
+                    INVESTOR_ADDRESS_RUNTIME_ARG => investor_address
+                },
+            );
+            store(INVESTOR_BALANCE, ret);
+        }
+        PURCHASED_TOKENS => {
+            let investor_address: Key = runtime::get_named_arg(INVESTOR_ADDRESS_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                PURCHASED_TOKENS,
+                runtime_args! {
+                    INVESTOR_ADDRESS_RUNTIME_ARG => investor_address
+                },
+            );
+            store(PURCHASED_TOKENS, ret);
+        }
+        QUOTE_TOKENS => {
+            let amount: U256 = runtime::get_named_arg(AMOUNT_RUNTIME_ARG);
+            let ret: (U256, U256) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                QUOTE_TOKENS,
+                runtime_args! {
+                    AMOUNT_RUNTIME_ARG => amount
+                },
+            );
+            store(QUOTE_TOKENS, ret);
+        }
+        DAILY_TOTALS => {
+            let day: u64 = runtime::get_named_arg(DAY_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                DAILY_TOTALS,
+                runtime_args! {
+                    DAY_RUNTIME_ARG => day
+                },
+            );
+            store(DAILY_TOTALS, ret);
+        }
+        INVESTOR_DAILY_BALANCE => {
+            let investor_address: Key = runtime::get_named_arg(INVESTOR_ADDRESS_RUNTIME_ARG);
+            let day: u64 = runtime::get_named_arg(DAY_RUNTIME_ARG);
+            let ret: (U256, U256, U256) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                INVESTOR_DAILY_BALANCE,
+                runtime_args! {
+                    INVESTOR_ADDRESS_RUNTIME_ARG => investor_address,
+                    DAY_RUNTIME_ARG => day
+                },
+            );
+            store(INVESTOR_DAILY_BALANCE, ret);
+        }
+        GLOBALS => {
+            let ret: ((U256, U256), (U256, U256), bool) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                GLOBALS,
+                runtime_args! {},
+            );
+            store(GLOBALS, ret);
+        }
+        CHECK_INVARIANTS => {
+            let offset: U256 = runtime::get_named_arg(OFFSET_RUNTIME_ARG);
+            let limit: U256 = runtime::get_named_arg(LIMIT_RUNTIME_ARG);
+            let totals: ((U256, U256, U256), (U256, U256)) =
+                runtime::get_named_arg(TOTALS_RUNTIME_ARG);
+            #[allow(clippy::type_complexity)]
+            let ret: (((U256, U256, U256), (U256, U256)), Option<bool>) =
+                runtime::call_versioned_contract(
+                    package_hash.into_hash().unwrap_or_revert().into(),
+                    None,
+                    CHECK_INVARIANTS,
+                    runtime_args! {
+                        OFFSET_RUNTIME_ARG => offset,
+                        LIMIT_RUNTIME_ARG => limit,
+                        TOTALS_RUNTIME_ARG => totals
+                    },
+                );
+            store(CHECK_INVARIANTS, ret);
+        }
+        UNIQUE_INVESTORS => {
+            let offset: U256 = runtime::get_named_arg(OFFSET_RUNTIME_ARG);
+            let limit: U256 = runtime::get_named_arg(LIMIT_RUNTIME_ARG);
+            let ret: Vec<Key> = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                UNIQUE_INVESTORS,
+                runtime_args! {
+                    OFFSET_RUNTIME_ARG => offset,
+                    LIMIT_RUNTIME_ARG => limit
+                },
+            );
+            store(UNIQUE_INVESTORS, ret);
+        }
+        INVESTOR_COUNTS => {
+            let ret: (U256, U256, U256) = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                INVESTOR_COUNTS,
+                runtime_args! {},
+            );
+            store(INVESTOR_COUNTS, ret);
+        }
+        REMAINING_ALLOWANCE => {
+            let investor_address: Key = runtime::get_named_arg(INVESTOR_ADDRESS_RUNTIME_ARG);
+            let ret: U256 = runtime::call_versioned_contract(
+                package_hash.into_hash().unwrap_or_revert().into(),
+                None,
+                REMAINING_ALLOWANCE,
+                runtime_args! {
+                    INVESTOR_ADDRESS_RUNTIME_ARG => investor_address
+                },
+            );
+            store(REMAINING_ALLOWANCE, ret);
+        }
         _ => runtime::revert(ApiError::MissingKey),
     };
 }
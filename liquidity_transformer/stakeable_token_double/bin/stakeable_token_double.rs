@@ -0,0 +1,430 @@
+#![no_main]
+#![no_std]
+
+// Minimal stakeable token implementing the interface the liquidity transformer
+// depends on, deployed by the transformer tests in place of the WISE contract.
+
+extern crate alloc;
+use alloc::{boxed::Box, collections::BTreeSet, format, string::ToString, vec, vec::Vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    account::AccountHash, runtime_args, ApiError, CLType, CLTyped, CLValue, ContractHash,
+    ContractPackageHash, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Group, Key,
+    Parameter, RuntimeArgs, URef, U256,
+};
+use casperlabs_contract_utils::{
+    get_key, key_to_str, set_key, ContractContext, Dict, OnChainContractStorage,
+};
+use casperlabs_erc20::ERC20;
+
+const MILLI_SECONDS_IN_DAY: u64 = 86_400_000;
+
+const OWNER: &str = "owner";
+const LAUNCH_TIME: &str = "launch_time";
+const LIQUIDITY_TRANSFORMER: &str = "liquidity_transformer";
+const TRANSFORMER_PURSE: &str = "transformer_purse";
+const STAKE_COUNT: &str = "stake_count";
+const STAKES: &str = "stakes";
+
+#[repr(u16)]
+enum Error {
+    NotOwner = 0,
+    NotTransformer = 1,
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> ApiError {
+        ApiError::User(error as u16)
+    }
+}
+
+#[derive(Default)]
+struct StakeableTokenDouble(OnChainContractStorage);
+
+impl ContractContext<OnChainContractStorage> for StakeableTokenDouble {
+    fn storage(&self) -> &OnChainContractStorage {
+        &self.0
+    }
+}
+
+impl ERC20<OnChainContractStorage> for StakeableTokenDouble {}
+
+impl StakeableTokenDouble {
+    fn constructor(&mut self, contract_hash: ContractHash, package_hash: ContractPackageHash) {
+        ERC20::init(
+            self,
+            "Stakeable Token".to_string(),
+            "WISE".to_string(),
+            9,
+            0.into(),
+            Key::from(contract_hash),
+            package_hash,
+        );
+        let time: u64 = runtime::get_blocktime().into();
+        set_key(OWNER, self.get_caller());
+        set_key(LAUNCH_TIME, time);
+        set_key(LIQUIDITY_TRANSFORMER, Key::from(AccountHash::default()));
+        set_key(STAKE_COUNT, 0_u32);
+        Dict::init(STAKES);
+    }
+
+    fn current_stakeable_day(&self) -> u64 {
+        let time: u64 = runtime::get_blocktime().into();
+        let launch_time: u64 = get_key(LAUNCH_TIME).unwrap_or_revert();
+        time.saturating_sub(launch_time) / MILLI_SECONDS_IN_DAY
+    }
+
+    fn only_owner(&self) {
+        if self.get_caller() != get_key::<Key>(OWNER).unwrap_or_revert() {
+            runtime::revert(ApiError::from(Error::NotOwner));
+        }
+    }
+
+    fn set_liquidity_transfomer(&mut self, immutable_transformer: Key, transformer_purse: URef) {
+        self.set_transformer(immutable_transformer);
+        set_key(TRANSFORMER_PURSE, transformer_purse);
+    }
+
+    // Unlike the real token the transformer can be replaced, so the tests can stand in for it
+    fn set_transformer(&mut self, transformer: Key) {
+        self.only_owner();
+        set_key(LIQUIDITY_TRANSFORMER, transformer);
+    }
+
+    fn liquidity_transformer(&self) -> Key {
+        get_key(LIQUIDITY_TRANSFORMER).unwrap_or_revert()
+    }
+
+    fn mint_supply(&mut self, investor_address: Key, amount: U256) {
+        if self.get_caller() != self.liquidity_transformer() {
+            runtime::revert(ApiError::from(Error::NotTransformer));
+        }
+        ERC20::mint(self, investor_address, amount);
+    }
+
+    // Burns the staked amount from the caller and records the stake under the staker
+    fn create_stake_for(
+        &mut self,
+        staker: Key,
+        staked_amount: U256,
+        lock_days: u64,
+        referrer: Key,
+    ) -> (Vec<u32>, U256, Vec<u32>) {
+        let caller: Key = self.get_caller();
+        ERC20::burn(self, caller, staked_amount);
+        let stake_count: u32 = get_key::<u32>(STAKE_COUNT)
+            .unwrap_or_revert()
+            .checked_add(1)
+            .unwrap_or_revert();
+        set_key(STAKE_COUNT, stake_count);
+        let stake_id: Vec<u32> = vec![stake_count];
+        let stakes = Dict::instance(STAKES);
+        let mut staker_stakes: Vec<(Vec<u32>, U256, (u64, Key))> =
+            stakes.get(&key_to_str(&staker)).unwrap_or_default();
+        staker_stakes.push((stake_id.clone(), staked_amount, (lock_days, referrer)));
+        stakes.set(&key_to_str(&staker), staker_stakes);
+        (stake_id, staked_amount, Vec::new())
+    }
+}
+
+#[no_mangle]
+fn constructor() {
+    let contract_hash: ContractHash = runtime::get_named_arg("contract_hash");
+    let package_hash: ContractPackageHash = runtime::get_named_arg("package_hash");
+    StakeableTokenDouble::default().constructor(contract_hash, package_hash);
+}
+
+/// @notice Returns whole days elapsed since the token was deployed
+#[no_mangle]
+fn current_stakeable_day() {
+    let ret: u64 = StakeableTokenDouble::default().current_stakeable_day();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Registers the transformer allowed to mint supply
+/// @dev can be only called by the deployer
+/// @param immutable_transformer package hash of the liquidity transformer
+/// @param transformer_purse purse of the liquidity transformer
+#[no_mangle]
+fn set_liquidity_transfomer() {
+    let immutable_transformer: Key = runtime::get_named_arg("immutable_transformer");
+    let transformer_purse: URef = runtime::get_named_arg("transformer_purse");
+    StakeableTokenDouble::default()
+        .set_liquidity_transfomer(immutable_transformer, transformer_purse);
+}
+
+/// @notice Replaces the transformer without a purse, used by the tests to act as the
+///     transformer towards SCSPR
+/// @dev can be only called by the deployer
+/// @param transformer address allowed to mint supply
+#[no_mangle]
+fn set_transformer() {
+    let transformer: Key = runtime::get_named_arg("transformer");
+    StakeableTokenDouble::default().set_transformer(transformer);
+}
+
+/// @notice Returns the transformer allowed to mint supply
+#[no_mangle]
+fn get_liquidity_transformer() {
+    let ret: Key = StakeableTokenDouble::default().liquidity_transformer();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Mints tokens for an investor
+/// @dev can be only called by the liquidity transformer
+/// @param investor_address address receiving the tokens
+/// @param amount amount of tokens to mint
+#[no_mangle]
+fn mint_supply() {
+    let investor_address: Key = runtime::get_named_arg("investor_address");
+    let amount: U256 = runtime::get_named_arg("amount");
+    StakeableTokenDouble::default().mint_supply(investor_address, amount);
+}
+
+/// @notice Mints tokens to any address, used by the tests to seed balances
+/// @param to address receiving the tokens
+/// @param amount amount of tokens to mint
+#[no_mangle]
+fn mint() {
+    let to: Key = runtime::get_named_arg("to");
+    let amount: U256 = runtime::get_named_arg("amount");
+    ERC20::mint(&mut StakeableTokenDouble::default(), to, amount);
+}
+
+/// @notice Burns tokens of the caller and opens a stake owned by staker
+/// @param staker owner of the stake
+/// @param staked_amount amount of tokens to stake
+/// @param lock_days amount of days the stake is locked for
+/// @param referrer referrer of the stake
+/// @return (stake_id, stake_shares, referral_id)
+#[no_mangle]
+fn create_stake_for() {
+    let staker: Key = runtime::get_named_arg("staker");
+    let staked_amount: U256 = runtime::get_named_arg("staked_amount");
+    let lock_days: u64 = runtime::get_named_arg("lock_days");
+    let referrer: Key = runtime::get_named_arg("referrer");
+    let ret: (Vec<u32>, U256, Vec<u32>) = StakeableTokenDouble::default().create_stake_for(
+        staker,
+        staked_amount,
+        lock_days,
+        referrer,
+    );
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice This function is to approve tokens against the address that user provided
+/// @param `spender` A Key that holds the account address of the user
+/// @param `amount` A U256 that holds the amount for approve
+#[no_mangle]
+fn approve() {
+    let spender: Key = runtime::get_named_arg("spender");
+    let amount: U256 = runtime::get_named_arg("amount");
+    StakeableTokenDouble::default().approve(spender, amount);
+}
+
+/// @notice This function is to transfer tokens against the address that user provided
+/// @param `recipient` A Key that holds the account address of the user
+/// @param `amount` A U256 that holds the amount for transfer
+#[no_mangle]
+fn transfer() {
+    let recipient: Key = runtime::get_named_arg("recipient");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let ret: Result<(), u32> = StakeableTokenDouble::default().transfer(recipient, amount);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice This function is to transfer tokens against the address that has been approved before by owner
+/// @param `owner` A Key that holds the account address of the user
+/// @param `recipient` A Key that holds the account address of the user
+/// @param `amount` A U256 that holds the amount for transfer
+#[no_mangle]
+fn transfer_from() {
+    let owner: Key = runtime::get_named_arg("owner");
+    let recipient: Key = runtime::get_named_arg("recipient");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let ret: Result<(), u32> =
+        StakeableTokenDouble::default().transfer_from(owner, recipient, amount);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice This function is to return the Balance  of owner against the address that user provided
+/// @param `owner` A Key that holds the account address of the user against which user wants to get balance
+#[no_mangle]
+fn balance_of() {
+    let owner: Key = runtime::get_named_arg("owner");
+    let ret: U256 = StakeableTokenDouble::default().balance_of(owner);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+fn get_entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        "constructor",
+        vec![
+            Parameter::new("contract_hash", ContractHash::cl_type()),
+            Parameter::new("package_hash", ContractPackageHash::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Groups(vec![Group::new("constructor")]),
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "current_stakeable_day",
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_liquidity_transfomer",
+        vec![
+            Parameter::new("immutable_transformer", Key::cl_type()),
+            Parameter::new("transformer_purse", URef::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "set_transformer",
+        vec![Parameter::new("transformer", Key::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "get_liquidity_transformer",
+        vec![],
+        Key::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "mint_supply",
+        vec![
+            Parameter::new("investor_address", Key::cl_type()),
+            Parameter::new("amount", U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "mint",
+        vec![
+            Parameter::new("to", Key::cl_type()),
+            Parameter::new("amount", U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "create_stake_for",
+        vec![
+            Parameter::new("staker", Key::cl_type()),
+            Parameter::new("staked_amount", U256::cl_type()),
+            Parameter::new("lock_days", u64::cl_type()),
+            Parameter::new("referrer", Key::cl_type()),
+        ],
+        CLType::Tuple3([
+            Box::new(CLType::List(Box::new(u32::cl_type()))),
+            Box::new(CLType::U256),
+            Box::new(CLType::List(Box::new(u32::cl_type()))),
+        ]),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "approve",
+        vec![
+            Parameter::new("spender", Key::cl_type()),
+            Parameter::new("amount", U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "transfer",
+        vec![
+            Parameter::new("recipient", Key::cl_type()),
+            Parameter::new("amount", U256::cl_type()),
+        ],
+        CLType::Result {
+            ok: Box::new(CLType::Unit),
+            err: Box::new(CLType::U32),
+        },
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "transfer_from",
+        vec![
+            Parameter::new("owner", Key::cl_type()),
+            Parameter::new("recipient", Key::cl_type()),
+            Parameter::new("amount", U256::cl_type()),
+        ],
+        CLType::Result {
+            ok: Box::new(CLType::Unit),
+            err: Box::new(CLType::U32),
+        },
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "balance_of",
+        vec![Parameter::new("owner", Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    // Store contract in the account's named keys. Contract name must be same for all new versions of the contracts
+    let contract_name: alloc::string::String = runtime::get_named_arg("contract_name");
+
+    let (package_hash, access_token) = storage::create_contract_package_at_hash();
+    let (contract_hash, _): (ContractHash, _) =
+        storage::add_contract_version(package_hash, get_entry_points(), Default::default());
+    let constructor_args = runtime_args! {
+        "contract_hash" => contract_hash,
+        "package_hash" => package_hash
+    };
+
+    // Add the constructor group to the package hash with a single URef.
+    let constructor_access: URef =
+        storage::create_contract_user_group(package_hash, "constructor", 1, Default::default())
+            .unwrap_or_revert()
+            .pop()
+            .unwrap_or_revert();
+
+    // Call the constructor entry point
+    let _: () =
+        runtime::call_versioned_contract(package_hash, None, "constructor", constructor_args);
+
+    // Remove all URefs from the constructor group, so no one can call it for the second time.
+    let mut urefs = BTreeSet::new();
+    urefs.insert(constructor_access);
+    storage::remove_contract_user_group_urefs(package_hash, "constructor", urefs)
+        .unwrap_or_revert();
+
+    runtime::put_key(
+        &format!("{}_package_hash", contract_name),
+        package_hash.into(),
+    );
+    runtime::put_key(
+        &format!("{}_contract_hash", contract_name),
+        contract_hash.into(),
+    );
+    runtime::put_key(
+        &format!("{}_package_access_token", contract_name),
+        access_token.into(),
+    );
+}
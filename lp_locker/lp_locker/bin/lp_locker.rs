@@ -0,0 +1,257 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::{collections::BTreeSet, format, vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    runtime_args, CLTyped, CLValue, ContractHash, ContractPackageHash, EntryPoint,
+    EntryPointAccess, EntryPointType, EntryPoints, Group, Key, Parameter, RuntimeArgs, URef, U256,
+};
+use casperlabs_contract_utils::{ContractContext, OnChainContractStorage};
+use lp_locker_crate::{self, data, LPLOCKER};
+
+#[derive(Default)]
+struct LpLocker(OnChainContractStorage);
+
+impl ContractContext<OnChainContractStorage> for LpLocker {
+    fn storage(&self) -> &OnChainContractStorage {
+        &self.0
+    }
+}
+
+impl LPLOCKER<OnChainContractStorage> for LpLocker {}
+
+impl LpLocker {
+    fn constructor(
+        &mut self,
+        lp_token: Key,
+        beneficiary: Key,
+        stakeable: Key,
+        unlock_day: u64,
+        package_hash: Key,
+        contract_hash: Key,
+    ) {
+        LPLOCKER::init(
+            self,
+            lp_token,
+            beneficiary,
+            stakeable,
+            unlock_day,
+            package_hash,
+            contract_hash,
+        );
+    }
+}
+
+#[no_mangle]
+fn constructor() {
+    let lp_token: Key = runtime::get_named_arg("lp_token");
+    let beneficiary: Key = runtime::get_named_arg("beneficiary");
+    let stakeable: Key = runtime::get_named_arg("stakeable");
+    let unlock_day: u64 = runtime::get_named_arg("unlock_day");
+    let package_hash: ContractPackageHash = runtime::get_named_arg("package_hash");
+    let contract_hash: ContractHash = runtime::get_named_arg("contract_hash");
+
+    LpLocker::default().constructor(
+        lp_token,
+        beneficiary,
+        stakeable,
+        unlock_day,
+        Key::from(package_hash),
+        Key::from(contract_hash),
+    );
+}
+
+/// @notice Transfers all locked LP tokens to the beneficiary
+/// @dev can be called by anyone once unlock_day is reached
+/// @return amount of LP tokens released
+#[no_mangle]
+fn release() {
+    let ret: U256 = LpLocker::default().release();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Amount of LP tokens currently held by the locker
+#[no_mangle]
+fn locked_amount() {
+    let ret: U256 = LpLocker::default().locked_amount();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+/// @notice Address of the locked LP token
+#[no_mangle]
+fn lp_token() {
+    runtime::ret(CLValue::from_t(data::lp_token()).unwrap_or_revert());
+}
+
+/// @notice Address receiving the LP tokens on release
+#[no_mangle]
+fn beneficiary() {
+    runtime::ret(CLValue::from_t(data::beneficiary()).unwrap_or_revert());
+}
+
+/// @notice Stakeable token used as the day clock
+#[no_mangle]
+fn stakeable() {
+    runtime::ret(CLValue::from_t(data::stakeable()).unwrap_or_revert());
+}
+
+/// @notice Stakeable day from which release is allowed
+#[no_mangle]
+fn unlock_day() {
+    runtime::ret(CLValue::from_t(data::unlock_day()).unwrap_or_revert());
+}
+
+fn get_entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        "constructor",
+        vec![
+            Parameter::new("lp_token", Key::cl_type()),
+            Parameter::new("beneficiary", Key::cl_type()),
+            Parameter::new("stakeable", Key::cl_type()),
+            Parameter::new("unlock_day", u64::cl_type()),
+            Parameter::new("package_hash", ContractPackageHash::cl_type()),
+            Parameter::new("contract_hash", ContractHash::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Groups(vec![Group::new("constructor")]),
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "release",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "locked_amount",
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "lp_token",
+        vec![],
+        Key::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "beneficiary",
+        vec![],
+        Key::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "stakeable",
+        vec![],
+        Key::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        "unlock_day",
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    // Store contract in the account's named keys. Contract name must be same for all new versions of the contracts
+    let contract_name: alloc::string::String = runtime::get_named_arg("contract_name");
+
+    // If this is the first deployment
+    if !runtime::has_key(&format!("{}_package_hash", contract_name)) {
+        // Build new package.
+        let (package_hash, access_token) = storage::create_contract_package_at_hash();
+        // add a first version to this package
+        let (contract_hash, _): (ContractHash, _) =
+            storage::add_contract_version(package_hash, get_entry_points(), Default::default());
+
+        let lp_token: Key = runtime::get_named_arg("lp_token");
+        let beneficiary: Key = runtime::get_named_arg("beneficiary");
+        let stakeable: Key = runtime::get_named_arg("stakeable");
+        let unlock_day: u64 = runtime::get_named_arg("unlock_day");
+        let constructor_args = runtime_args! {
+            "lp_token" => lp_token,
+            "beneficiary" => beneficiary,
+            "stakeable" => stakeable,
+            "unlock_day" => unlock_day,
+            "package_hash" => package_hash,
+            "contract_hash" => contract_hash
+        };
+
+        // Add the constructor group to the package hash with a single URef.
+        let constructor_access: URef =
+            storage::create_contract_user_group(package_hash, "constructor", 1, Default::default())
+                .unwrap_or_revert()
+                .pop()
+                .unwrap_or_revert();
+
+        // Call the constructor entry point
+        let _: () =
+            runtime::call_versioned_contract(package_hash, None, "constructor", constructor_args);
+
+        // Remove all URefs from the constructor group, so no one can call it for the second time.
+        let mut urefs = BTreeSet::new();
+        urefs.insert(constructor_access);
+        storage::remove_contract_user_group_urefs(package_hash, "constructor", urefs)
+            .unwrap_or_revert();
+
+        runtime::put_key(
+            &format!("{}_package_hash", contract_name),
+            package_hash.into(),
+        );
+        runtime::put_key(
+            &format!("{}_package_hash_wrapped", contract_name),
+            storage::new_uref(package_hash).into(),
+        );
+        runtime::put_key(
+            &format!("{}_contract_hash", contract_name),
+            contract_hash.into(),
+        );
+        runtime::put_key(
+            &format!("{}_contract_hash_wrapped", contract_name),
+            storage::new_uref(contract_hash).into(),
+        );
+        runtime::put_key(
+            &format!("{}_package_access_token", contract_name),
+            access_token.into(),
+        );
+    }
+    // If contract package did already exist
+    else {
+        // get the package
+        let package_hash: ContractPackageHash =
+            runtime::get_key(&format!("{}_package_hash", contract_name))
+                .unwrap_or_revert()
+                .into_hash()
+                .unwrap()
+                .into();
+        // create new version and install it
+        let (contract_hash, _): (ContractHash, _) =
+            storage::add_contract_version(package_hash, get_entry_points(), Default::default());
+
+        // update contract hash
+        runtime::put_key(
+            &format!("{}_contract_hash", contract_name),
+            contract_hash.into(),
+        );
+        runtime::put_key(
+            &format!("{}_contract_hash_wrapped", contract_name),
+            storage::new_uref(contract_hash).into(),
+        );
+    }
+}
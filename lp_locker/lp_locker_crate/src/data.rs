@@ -0,0 +1,63 @@
+use casper_types::Key;
+use casperlabs_contract_utils::{get_key, set_key};
+
+pub const LP_TOKEN: &str = "lp_token";
+pub const BENEFICIARY: &str = "beneficiary";
+pub const STAKEABLE: &str = "stakeable";
+pub const UNLOCK_DAY: &str = "unlock_day";
+
+pub const SELF_CONTRACT_HASH: &str = "self_contract_hash";
+pub const SELF_PACKAGE_HASH: &str = "self_package_hash";
+
+pub fn zero_address() -> Key {
+    Key::from_formatted_str("hash-0000000000000000000000000000000000000000000000000000000000000000")
+        .unwrap()
+}
+
+pub fn lp_token() -> Key {
+    get_key(LP_TOKEN).unwrap_or_else(zero_address)
+}
+
+pub fn set_lp_token(lp_token: Key) {
+    set_key(LP_TOKEN, lp_token);
+}
+
+pub fn beneficiary() -> Key {
+    get_key(BENEFICIARY).unwrap_or_else(zero_address)
+}
+
+pub fn set_beneficiary(beneficiary: Key) {
+    set_key(BENEFICIARY, beneficiary);
+}
+
+pub fn stakeable() -> Key {
+    get_key(STAKEABLE).unwrap_or_else(zero_address)
+}
+
+pub fn set_stakeable(stakeable: Key) {
+    set_key(STAKEABLE, stakeable);
+}
+
+pub fn unlock_day() -> u64 {
+    get_key(UNLOCK_DAY).unwrap_or_default()
+}
+
+pub fn set_unlock_day(unlock_day: u64) {
+    set_key(UNLOCK_DAY, unlock_day);
+}
+
+pub fn hash() -> Key {
+    get_key(SELF_CONTRACT_HASH).unwrap_or_else(zero_address)
+}
+
+pub fn set_hash(contract_hash: Key) {
+    set_key(SELF_CONTRACT_HASH, contract_hash);
+}
+
+pub fn package() -> Key {
+    get_key(SELF_PACKAGE_HASH).unwrap_or_else(zero_address)
+}
+
+pub fn set_package(package_hash: Key) {
+    set_key(SELF_PACKAGE_HASH, package_hash);
+}
@@ -0,0 +1,8 @@
+#![no_std]
+
+extern crate alloc;
+
+pub mod data;
+mod lp_locker;
+
+pub use lp_locker::LPLOCKER;
@@ -0,0 +1,139 @@
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{runtime_args, ApiError, Key, RuntimeArgs, URef, U256};
+use casperlabs_contract_utils::{ContractContext, ContractStorage};
+
+use crate::data;
+
+#[repr(u16)]
+pub enum Error {
+    StillLocked = 0,
+    NothingToRelease,
+    TransferFailed,
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> ApiError {
+        ApiError::User(error as u16)
+    }
+}
+
+pub enum LpLockerEvent {
+    Released {
+        lp_token: Key,
+        beneficiary: Key,
+        amount: U256,
+    },
+}
+
+impl LpLockerEvent {
+    pub fn type_name(&self) -> String {
+        match self {
+            LpLockerEvent::Released {
+                lp_token: _,
+                beneficiary: _,
+                amount: _,
+            } => "released",
+        }
+        .to_string()
+    }
+}
+
+pub trait LPLOCKER<Storage: ContractStorage>: ContractContext<Storage> {
+    fn init(
+        &self,
+        lp_token: Key,
+        beneficiary: Key,
+        stakeable: Key,
+        unlock_day: u64,
+        package_hash: Key,
+        contract_hash: Key,
+    ) {
+        data::set_lp_token(lp_token);
+        data::set_beneficiary(beneficiary);
+        data::set_stakeable(stakeable);
+        data::set_unlock_day(unlock_day);
+        data::set_hash(contract_hash);
+        data::set_package(package_hash);
+    }
+
+    fn current_stakeable_day(&self) -> u64 {
+        runtime::call_versioned_contract(
+            data::stakeable().into_hash().unwrap_or_revert().into(),
+            None,
+            "current_stakeable_day",
+            runtime_args! {},
+        )
+    }
+
+    fn locked_amount(&self) -> U256 {
+        runtime::call_versioned_contract(
+            data::lp_token().into_hash().unwrap_or_revert().into(),
+            None,
+            "balance_of",
+            runtime_args! {
+                "owner" => data::package()
+            },
+        )
+    }
+
+    fn release(&mut self) -> U256 {
+        if self.current_stakeable_day() < data::unlock_day() {
+            runtime::revert(ApiError::from(Error::StillLocked));
+        }
+        let amount: U256 = self.locked_amount();
+        if amount == U256::from(0) {
+            runtime::revert(ApiError::from(Error::NothingToRelease));
+        }
+        let ret: Result<(), u32> = runtime::call_versioned_contract(
+            data::lp_token().into_hash().unwrap_or_revert().into(),
+            None,
+            "transfer",
+            runtime_args! {
+                "recipient" => data::beneficiary(),
+                "amount" => amount
+            },
+        );
+        if ret.is_err() {
+            runtime::revert(ApiError::from(Error::TransferFailed));
+        }
+        self.emit(&LpLockerEvent::Released {
+            lp_token: data::lp_token(),
+            beneficiary: data::beneficiary(),
+            amount,
+        });
+        amount
+    }
+
+    fn emit(&mut self, lp_locker_event: &LpLockerEvent) {
+        let mut events = Vec::new();
+        let tmp = data::package().to_formatted_string();
+        let tmp: Vec<&str> = tmp.split('-').collect();
+        let package_hash = tmp[1].to_string();
+        match lp_locker_event {
+            LpLockerEvent::Released {
+                lp_token,
+                beneficiary,
+                amount,
+            } => {
+                let mut event = BTreeMap::new();
+                event.insert("contract_package_hash", package_hash);
+                event.insert("event_type", lp_locker_event.type_name());
+                event.insert("lp_token", lp_token.to_string());
+                event.insert("beneficiary", beneficiary.to_string());
+                event.insert("amount", amount.to_string());
+                events.push(event);
+            }
+        };
+        for event in events {
+            let _: URef = storage::new_uref(event);
+        }
+    }
+}
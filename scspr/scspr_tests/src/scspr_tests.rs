@@ -20,6 +20,12 @@ const FIFTY_CSPR: U256 = U256([50_000_000_000, 0, 0, 0]);
 const TWOHUNDRET_CSPR: U256 = U256([200_000_000_000, 0, 0, 0]);
 pub const STAKEABLE_AMOUNT: U512 = U512([0, 0, 0, 0, 0, 0, 0, 0]);
 
+const INVESTMENT_DAYS: u8 = 15;
+const MAX_SUPPLY: U256 = U256([264_000_000_000_000_000, 0, 0, 0]); // 264000000E9
+const MAX_INVEST: U256 = U256([200_000_000_000_000, 0, 0, 0]); // 200000E9
+const TOKEN_COST: U256 = U256([757_575, 0, 0, 0]); // MAX_INVEST / (MAX_SUPPLY / 1E9)
+const REFUND_CAP: U256 = U256([100_000_000_000, 0, 0, 0]); // 100E9
+
 pub fn now() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -245,6 +251,13 @@ pub fn deploy_liquidity_transformer(
             "pair_scspr" => pair_scspr,
             "uniswap_router" => uniswap_router,
             "wcspr" => wcspr,
+            "investment_days" => INVESTMENT_DAYS,
+            "max_supply" => MAX_SUPPLY,
+            "max_invest" => MAX_INVEST,
+            "token_cost" => TOKEN_COST,
+            "refund_cap" => REFUND_CAP,
+            "vesting_cliff_days" => 0_u64,
+            "vesting_days" => 0_u64,
             "amount" => amount
         },
         time,
@@ -4,7 +4,7 @@
 // We need to explicitly import the std alloc crate and `alloc::string::String` as we're in a
 // `no_std` environment.
 extern crate alloc;
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use casper_contract::{
     contract_api::{account, runtime, storage, system},
     unwrap_or_revert::UnwrapOrRevert,
@@ -56,6 +56,10 @@ pub extern "C" fn call() {
                 runtime_args! {
                     "investment_mode" => investment_mode,
                     "msg_value" => <casper_types::U512 as AsPrimitive<casper_types::U256>>::as_(amount),
+                    "referral_address" => None::<Key>,
+                    "beneficiary" => None::<Key>,
+                    "kyc_approval" => None::<((U256, u64, U256), String)>,
+                    "allowlist_proof" => None::<(U256, Vec<String>)>,
                     "caller_purse" => purse
                 },
             );